@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use image::ImageResult;
+
+use crate::color::LinearRgb;
+use crate::film::save_linear;
+use crate::objects::hittable::HitRecord;
+use crate::ray::Ray;
+use crate::tone_mapping::srgb_oetf;
+use crate::vec3::{Point3, Vec3};
+
+/// An arbitrary output variable, an image recorded beside the rendered one from what the
+/// camera rays hit first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Aov {
+    /// The surface color with no lighting, see `Material::albedo`.
+    Albedo,
+    /// The world space shading normal.
+    Normal,
+    /// The distance from the camera along its viewing direction, infinite where nothing is hit.
+    Depth,
+    /// The world space position.
+    Position,
+    /// Each material gets a number starting from one, in the order they first show up in the
+    /// image, zero where nothing is hit.
+    MaterialId,
+    /// One more than the index of the object in the scene, zero where nothing is hit.
+    ObjectId,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 6] = [
+        Aov::Albedo,
+        Aov::Normal,
+        Aov::Depth,
+        Aov::Position,
+        Aov::MaterialId,
+        Aov::ObjectId,
+    ];
+
+    /// The name of the layer, as used in file and channel names.
+    #[inline(always)]
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::Position => "position",
+            Aov::MaterialId => "materialId",
+            Aov::ObjectId => "objectId",
+        }
+    }
+
+    /// The names of the channels in the layer.
+    #[inline(always)]
+    pub fn get_channels(&self) -> &'static [&'static str] {
+        match self {
+            Aov::Albedo => &["R", "G", "B"],
+            Aov::Normal | Aov::Position => &["X", "Y", "Z"],
+            Aov::Depth => &["Z"],
+            Aov::MaterialId | Aov::ObjectId => &["id"],
+        }
+    }
+}
+
+/// What one camera ray hit first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AovSample {
+    albedo: Vec3,
+    normal: Vec3,
+    position: Point3,
+    depth: f64,
+    material: usize,
+    object_id: Option<usize>,
+}
+
+impl AovSample {
+    /// Records `rec`, the first hit along the camera ray `r`. Depth is measured along
+    /// `forward`, the direction the camera looks in.
+    pub fn from_hit(r: &Ray, rec: &HitRecord, forward: Vec3) -> AovSample {
+        let material = rec.get_material();
+        AovSample {
+            albedo: material.albedo(rec),
+            normal: rec.get_normal(),
+            position: rec.get_point(),
+            depth: (rec.get_point() - r.get_origin()).dot(&forward.unit_vector()),
+            // Materials have no names, the address tells them apart until they are numbered.
+            material: Arc::as_ptr(&material) as *const () as usize,
+            object_id: rec.get_object_id(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_albedo(&self) -> Vec3 {
+        self.albedo
+    }
+
+    #[inline(always)]
+    pub fn get_normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    #[inline(always)]
+    pub fn get_position(&self) -> Point3 {
+        self.position
+    }
+
+    #[inline(always)]
+    pub fn get_depth(&self) -> f64 {
+        self.depth
+    }
+
+    #[inline(always)]
+    pub fn get_object_id(&self) -> Option<usize> {
+        self.object_id
+    }
+}
+
+/// The AOV samples that landed in one pixel. Albedo and normal are averaged over every sample,
+/// misses counting as zero, so edges are antialiased like the rendered image. Depth and
+/// position are averaged over the samples that hit something, and the IDs come from the first
+/// of those.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AovPixel {
+    albedo: Vec3,
+    normal: Vec3,
+    position: Vec3,
+    depth: f64,
+    count: u32,
+    hits: u32,
+    material: Option<usize>,
+    object_id: Option<usize>,
+}
+
+impl AovPixel {
+    /// Adds what one camera ray hit first, `None` if it missed everything.
+    pub fn add_sample(&mut self, sample: Option<&AovSample>) {
+        self.count += 1;
+        let sample = match sample {
+            Some(s) => s,
+            None => return,
+        };
+        if self.hits == 0 {
+            self.material = Some(sample.material);
+            self.object_id = sample.object_id;
+        }
+        self.hits += 1;
+        self.albedo += sample.albedo;
+        self.normal += sample.normal;
+        self.position += sample.position;
+        self.depth += sample.depth;
+    }
+
+    #[inline(always)]
+    pub fn get_sample_count(&self) -> u32 {
+        self.count
+    }
+
+    #[inline(always)]
+    pub fn get_albedo(&self) -> Vec3 {
+        self.albedo / self.count.max(1) as f64
+    }
+
+    /// The mean normal, shorter than one where the samples disagree or missed.
+    #[inline(always)]
+    pub fn get_normal(&self) -> Vec3 {
+        self.normal / self.count.max(1) as f64
+    }
+
+    #[inline(always)]
+    pub fn get_position(&self) -> Point3 {
+        self.position / self.hits.max(1) as f64
+    }
+
+    #[inline(always)]
+    pub fn get_depth(&self) -> f64 {
+        if self.hits == 0 {
+            f64::INFINITY
+        } else {
+            self.depth / self.hits as f64
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_object_id(&self) -> Option<usize> {
+        self.object_id
+    }
+}
+
+impl Default for AovPixel {
+    fn default() -> AovPixel {
+        AovPixel {
+            albedo: Vec3::new(0.0, 0.0, 0.0),
+            normal: Vec3::new(0.0, 0.0, 0.0),
+            position: Vec3::new(0.0, 0.0, 0.0),
+            depth: 0.0,
+            count: 0,
+            hits: 0,
+            material: None,
+            object_id: None,
+        }
+    }
+}
+
+/// The AOVs of a whole image, laid out like a `Film`, in rows from the top down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AovBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<AovPixel>,
+}
+
+impl AovBuffer {
+    pub fn new(width: usize, height: usize) -> AovBuffer {
+        AovBuffer {
+            width,
+            height,
+            pixels: vec![AovPixel::default(); width * height],
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    #[inline(always)]
+    pub fn get_pixel(&self, x: usize, y: usize) -> &AovPixel {
+        &self.pixels[(y * self.width) + x]
+    }
+
+    #[inline(always)]
+    pub fn get_pixels(&self) -> &[AovPixel] {
+        &self.pixels
+    }
+
+    /// The pixels in row order, for filling rows in parallel.
+    #[inline(always)]
+    pub fn get_pixels_mut(&mut self) -> &mut [AovPixel] {
+        &mut self.pixels
+    }
+
+    /// The values of `aov` in row order, one vector per channel.
+    pub fn get_channel_values(&self, aov: Aov) -> Vec<Vec<f32>> {
+        let xyz = |f: fn(&AovPixel) -> Vec3| {
+            let values: Vec<Vec3> = self.pixels.iter().map(f).collect();
+            vec![
+                values.iter().map(|v| v.get_x() as f32).collect(),
+                values.iter().map(|v| v.get_y() as f32).collect(),
+                values.iter().map(|v| v.get_z() as f32).collect(),
+            ]
+        };
+        match aov {
+            Aov::Albedo => xyz(AovPixel::get_albedo),
+            Aov::Normal => xyz(AovPixel::get_normal),
+            Aov::Position => xyz(AovPixel::get_position),
+            Aov::Depth => vec![self.pixels.iter().map(|p| p.get_depth() as f32).collect()],
+            Aov::MaterialId => {
+                let mut ids = HashMap::new();
+                let values = self
+                    .pixels
+                    .iter()
+                    .map(|p| match p.material {
+                        Some(m) => {
+                            let next = ids.len() + 1;
+                            *ids.entry(m).or_insert(next) as f32
+                        }
+                        None => 0.0,
+                    })
+                    .collect();
+                vec![values]
+            }
+            Aov::ObjectId => vec![self
+                .pixels
+                .iter()
+                .map(|p| p.object_id.map_or(0.0, |id| (id + 1) as f32))
+                .collect()],
+        }
+    }
+
+    /// Every AOV as channels named like `albedo.R`, to store as layers of an OpenEXR file.
+    pub fn to_layers(&self) -> Vec<(String, Vec<f32>)> {
+        Aov::ALL
+            .iter()
+            .flat_map(|aov| {
+                aov.get_channels()
+                    .iter()
+                    .zip(self.get_channel_values(*aov))
+                    .map(move |(channel, values)| {
+                        (format!("{}.{}", aov.get_name(), channel), values)
+                    })
+            })
+            .collect()
+    }
+
+    /// The values of `aov` as pixels, single channels repeated in all three.
+    pub fn to_linear(&self, aov: Aov) -> Vec<LinearRgb> {
+        let channels = self.get_channel_values(aov);
+        let channel = |i: usize| &channels[i.min(channels.len() - 1)];
+        (0..self.pixels.len())
+            .map(|i| LinearRgb::new(channel(0)[i], channel(1)[i], channel(2)[i]))
+            .collect()
+    }
+
+    /// Writes `aov` on its own, in the format given by the extension of `path`. Floating point
+    /// formats get the raw values. 8-bit ones get something to look at: normals mapped from
+    /// `[-1, 1]`, depth and position scaled to the range in the image and a color per ID.
+    pub fn save<P: AsRef<Path>>(&self, path: P, aov: Aov) -> ImageResult<()> {
+        let pixels = self.to_linear(aov);
+        let (lower, upper) = pixels
+            .iter()
+            .zip(&self.pixels)
+            .filter(|(_, p)| p.hits > 0)
+            .fold(
+                (
+                    LinearRgb::new(f32::MAX, f32::MAX, f32::MAX),
+                    LinearRgb::new(f32::MIN, f32::MIN, f32::MIN),
+                ),
+                |(lower, upper), (c, _)| {
+                    (
+                        LinearRgb::new(
+                            lower.get_red().min(c.get_red()),
+                            lower.get_green().min(c.get_green()),
+                            lower.get_blue().min(c.get_blue()),
+                        ),
+                        LinearRgb::new(
+                            upper.get_red().max(c.get_red()),
+                            upper.get_green().max(c.get_green()),
+                            upper.get_blue().max(c.get_blue()),
+                        ),
+                    )
+                },
+            );
+        let scale = |x: f32, lower: f32, upper: f32| (x - lower) / (upper - lower).max(1e-6);
+        save_linear(path, self.width, self.height, &pixels, |c| match aov {
+            Aov::Albedo => c.map(srgb_oetf),
+            Aov::Normal => c.map(|x| (0.5 * x) + 0.5),
+            Aov::Depth => c.map(|x| x / upper.get_red().max(1e-6)),
+            Aov::Position => LinearRgb::new(
+                scale(c.get_red(), lower.get_red(), upper.get_red()),
+                scale(c.get_green(), lower.get_green(), upper.get_green()),
+                scale(c.get_blue(), lower.get_blue(), upper.get_blue()),
+            ),
+            Aov::MaterialId | Aov::ObjectId => id_color(c.get_red() as u32),
+        })
+    }
+}
+
+// A color that tells neighbouring IDs apart, black for zero.
+fn id_color(id: u32) -> LinearRgb {
+    if id == 0 {
+        return LinearRgb::default();
+    }
+    let hash = id.wrapping_mul(0x9e37_79b9);
+    let byte = |shift: u32| (((hash >> shift) & 0xff) as f32 / 255.0).max(0.2);
+    LinearRgb::new(byte(24), byte(16), byte(8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::hittable::Hittable;
+    use crate::objects::hittable_list::HittableList;
+    use crate::objects::lambertian::Lambertian;
+    use crate::objects::sphere::Sphere;
+
+    fn first_hit(world: &HittableList, x: f64) -> Option<AovSample> {
+        let r = Ray::new(Point3::new(x, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0), 0.0);
+        world
+            .hit(&r, 0.001, f64::INFINITY)
+            .map(|rec| AovSample::from_hit(&r, &rec, Vec3::new(0.0, 0.0, -1.0)))
+    }
+
+    fn two_spheres() -> HittableList {
+        let red = Arc::new(Lambertian::new(Color::new(255, 0, 0)));
+        let blue = Arc::new(Lambertian::new(Color::new(0, 0, 255)));
+        let mut world = HittableList::new();
+        world.add(Arc::new(Sphere::new_immobile(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            red,
+        )));
+        world.add(Arc::new(Sphere::new_immobile(
+            Point3::new(4.0, 0.0, 0.0),
+            1.0,
+            blue,
+        )));
+        world
+    }
+
+    #[test]
+    fn test_first_hit() {
+        let world = two_spheres();
+        let sample = first_hit(&world, 4.0).unwrap();
+        assert_eq!(sample.get_albedo(), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(sample.get_normal(), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(sample.get_position(), Point3::new(4.0, 0.0, 1.0));
+        assert_eq!(sample.get_depth(), 4.0);
+        assert_eq!(sample.get_object_id(), Some(1));
+        assert!(first_hit(&world, 2.0).is_none());
+    }
+
+    #[test]
+    fn test_pixels_average_hits_and_misses() {
+        let world = two_spheres();
+        let mut pixel = AovPixel::default();
+        pixel.add_sample(first_hit(&world, 0.0).as_ref());
+        pixel.add_sample(None);
+        assert_eq!(pixel.get_sample_count(), 2);
+        assert_eq!(pixel.get_albedo(), Vec3::new(0.5, 0.0, 0.0));
+        assert_eq!(pixel.get_normal(), Vec3::new(0.0, 0.0, 0.5));
+        assert_eq!(pixel.get_depth(), 4.0);
+        assert_eq!(pixel.get_object_id(), Some(0));
+        assert_eq!(AovPixel::default().get_depth(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_layers() {
+        let world = two_spheres();
+        let mut buffer = AovBuffer::new(3, 1);
+        for (pixel, x) in buffer.get_pixels_mut().iter_mut().zip(&[4.0, 2.0, 0.0]) {
+            pixel.add_sample(first_hit(&world, *x).as_ref());
+        }
+        let layers = buffer.to_layers();
+        let names: Vec<&str> = layers.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names.len(), 12);
+        assert!(names.contains(&"albedo.G") && names.contains(&"depth.Z"));
+        let layer = |name: &str| &layers.iter().find(|(n, _)| n == name).unwrap().1;
+        // Materials are numbered in the order they show up, objects by their index.
+        assert_eq!(layer("materialId.id"), &[1.0, 0.0, 2.0]);
+        assert_eq!(layer("objectId.id"), &[2.0, 0.0, 1.0]);
+        assert_eq!(layer("depth.Z")[1], f32::INFINITY);
+        assert_eq!(
+            buffer.to_linear(Aov::Depth)[0],
+            LinearRgb::new(4.0, 4.0, 4.0)
+        );
+    }
+}
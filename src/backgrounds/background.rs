@@ -0,0 +1,20 @@
+use crate::vec3::Vec3;
+
+/// What a ray sees when it leaves the scene without hitting anything.
+pub trait Background {
+    /// Radiance arriving from infinitely far away along `-direction`, i.e. seen by a ray
+    /// travelling in `direction`. `direction` need not be normalized.
+    fn radiance(&self, direction: Vec3) -> Vec3;
+
+    /// Picks a direction with probability roughly proportional to the radiance from it, given
+    /// two uniform numbers in `[0, 1)`. Returns the unit direction and its solid angle density,
+    /// or `None` for backgrounds that are not worth importance sampling.
+    fn sample_direction(&self, _u: (f64, f64)) -> Option<(Vec3, f64)> {
+        None
+    }
+
+    /// The solid angle density with which `sample_direction` picks `direction`.
+    fn pdf_direction(&self, _direction: Vec3) -> f64 {
+        0.0
+    }
+}
@@ -0,0 +1,285 @@
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use image::codecs::hdr::HdrDecoder;
+use image::ImageResult;
+
+use crate::sampling::Distribution2D;
+use crate::vec3::Vec3;
+
+use crate::backgrounds::background::Background;
+
+/// An equirectangular (latitude-longitude) image surrounding the scene. The top row is straight
+/// up and the middle column faces +x, matching `Sphere::get_sphere_uv`.
+#[derive(Clone, Debug)]
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+    distribution: Distribution2D,
+    rotation: f64,
+    intensity: f64,
+}
+
+impl EnvironmentMap {
+    /// `pixels` are linear radiance in row-major order, top row first.
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> EnvironmentMap {
+        assert!(width > 0 && height > 0, "environment map is empty");
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "pixel count does not match size"
+        );
+        // Weight each pixel by brightness and by the solid angle it covers, rows near the poles
+        // are squeezed into much less of the sphere.
+        let weights: Vec<f64> = pixels
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let v = 1.0 - (((i / width) as f64 + 0.5) / height as f64);
+                p.luminance() * (v * PI).sin()
+            })
+            .collect();
+        EnvironmentMap {
+            width,
+            height,
+            distribution: Distribution2D::new(&weights, width, height),
+            pixels,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// Loads a Radiance `.hdr` file.
+    pub fn open<P: AsRef<Path>>(path: P) -> ImageResult<EnvironmentMap> {
+        let file = File::open(path)?;
+        let decoder = HdrDecoder::new(BufReader::new(file))?;
+        let meta = decoder.metadata();
+        let pixels = decoder
+            .read_image_hdr()?
+            .into_iter()
+            .map(|p| Vec3::new(p[0] as f64, p[1] as f64, p[2] as f64))
+            .collect();
+        Ok(EnvironmentMap::new(
+            meta.width as usize,
+            meta.height as usize,
+            pixels,
+        ))
+    }
+
+    /// Turns the map counter-clockwise about the y axis, seen from above.
+    #[inline(always)]
+    pub fn with_rotation(mut self, degrees: f64) -> EnvironmentMap {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Scales every pixel, for maps stored in arbitrary units.
+    #[inline(always)]
+    pub fn with_intensity(mut self, intensity: f64) -> EnvironmentMap {
+        self.intensity = intensity;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    #[inline(always)]
+    pub fn get_intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    /// Image coordinates in `[0, 1]` with `v` up for a world space direction.
+    pub fn direction_to_uv(&self, direction: Vec3) -> (f64, f64) {
+        let d = direction.unit_vector();
+        let phi = (-d.get_z()).atan2(d.get_x()) + PI - self.rotation.to_radians();
+        let theta = (-d.get_y()).clamp(-1.0, 1.0).acos();
+        ((phi / (2.0 * PI)).rem_euclid(1.0), theta / PI)
+    }
+
+    /// The unit direction that `direction_to_uv` maps to `(u, v)`.
+    pub fn uv_to_direction(&self, u: f64, v: f64) -> Vec3 {
+        let phi = (u * 2.0 * PI) - PI + self.rotation.to_radians();
+        let theta = v * PI;
+        Vec3::new(
+            phi.cos() * theta.sin(),
+            -theta.cos(),
+            -phi.sin() * theta.sin(),
+        )
+    }
+
+    #[inline(always)]
+    fn pixel(&self, x: i64, y: i64) -> Vec3 {
+        // Longitude wraps around, latitude stops at the poles.
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.clamp(0, self.height as i64 - 1) as usize;
+        self.pixels[(y * self.width) + x]
+    }
+
+    /// Bilinearly filtered radiance at image coordinates `(u, v)`, including the intensity.
+    pub fn lookup(&self, u: f64, v: f64) -> Vec3 {
+        let x = (u * self.width as f64) - 0.5;
+        let y = ((1.0 - v) * self.height as f64) - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = ((1.0 - fx) * self.pixel(x0, y0)) + (fx * self.pixel(x0 + 1, y0));
+        let bottom = ((1.0 - fx) * self.pixel(x0, y0 + 1)) + (fx * self.pixel(x0 + 1, y0 + 1));
+        self.intensity * (((1.0 - fy) * top) + (fy * bottom))
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn radiance(&self, direction: Vec3) -> Vec3 {
+        let (u, v) = self.direction_to_uv(direction);
+        self.lookup(u, v)
+    }
+
+    fn sample_direction(&self, u: (f64, f64)) -> Option<(Vec3, f64)> {
+        // The distribution runs top to bottom like the pixels, v runs bottom to top.
+        let ((x, y), pdf) = self.distribution.sample_continuous(u);
+        let v = 1.0 - y;
+        let sin_theta = (v * PI).sin();
+        if pdf <= 0.0 || sin_theta <= 0.0 {
+            return None;
+        }
+        // The image maps onto the sphere with a Jacobian of 2 pi^2 sin(theta).
+        Some((
+            self.uv_to_direction(x, v),
+            pdf / (2.0 * PI * PI * sin_theta),
+        ))
+    }
+
+    fn pdf_direction(&self, direction: Vec3) -> f64 {
+        let (u, v) = self.direction_to_uv(direction);
+        let sin_theta = (v * PI).sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        self.distribution.pdf((u, 1.0 - v)) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::codecs::hdr::HdrEncoder;
+    use image::Rgb;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_direction_uv_round_trip() {
+        let env = EnvironmentMap::new(1, 1, vec![Vec3::new(1.0, 1.0, 1.0)]).with_rotation(30.0);
+        for d in &[
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.3, 0.4, -0.8),
+            Vec3::new(-0.5, -0.7, 0.2),
+        ] {
+            let (u, v) = env.direction_to_uv(*d);
+            assert_near(env.uv_to_direction(u, v), d.unit_vector());
+        }
+    }
+
+    #[test]
+    fn test_rotation_and_intensity() {
+        // Bright sky over a dark ground, with a red column facing +x.
+        let mut pixels = vec![Vec3::new(0.0, 0.0, 0.0); 8 * 4];
+        for (i, p) in pixels.iter_mut().enumerate() {
+            if i / 8 < 2 {
+                *p = Vec3::new(2.0, 2.0, 2.0);
+            }
+        }
+        pixels[(2 * 8) + 4] = Vec3::new(5.0, 0.0, 0.0);
+        pixels[(2 * 8) + 3] = Vec3::new(5.0, 0.0, 0.0);
+        let env = EnvironmentMap::new(8, 4, pixels).with_intensity(0.5);
+        assert_near(
+            env.radiance(Vec3::new(0.0, 1.0, 0.0)),
+            Vec3::new(1.0, 1.0, 1.0),
+        );
+        // Straight through the center of the red texels.
+        let (sin, cos) = (0.375 * PI).sin_cos();
+        assert_near(
+            env.radiance(Vec3::new(sin, -cos, 0.0)),
+            Vec3::new(2.5, 0.0, 0.0),
+        );
+
+        // A quarter turn brings the red column round to -z.
+        let env = env.with_rotation(90.0);
+        assert_near(
+            env.radiance(Vec3::new(0.0, -cos, -sin)),
+            Vec3::new(2.5, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn test_sampling_favors_bright_pixels() {
+        // A dim map with one bright texel.
+        let mut pixels = vec![Vec3::new(0.1, 0.1, 0.1); 16 * 8];
+        pixels[(3 * 16) + 5] = Vec3::new(1000.0, 1000.0, 1000.0);
+        let env = EnvironmentMap::new(16, 8, pixels).with_rotation(45.0);
+        let bright = env.uv_to_direction(5.5 / 16.0, 1.0 - (3.5 / 8.0));
+
+        let n = 32;
+        let mut near_bright = 0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = ((i as f64 + 0.5) / n as f64, (j as f64 + 0.5) / n as f64);
+                let (direction, pdf) = env.sample_direction(u).unwrap();
+                assert!((env.pdf_direction(direction) - pdf).abs() < 1e-6 * pdf);
+                if direction.dot(&bright) > 0.95 {
+                    near_bright += 1;
+                }
+            }
+        }
+        assert!(near_bright > (n * n) * 9 / 10);
+    }
+
+    #[test]
+    fn test_pdf_integrates_to_one() {
+        let pixels = (0..8 * 4).map(|i| Vec3::new(i as f64, 1.0, 0.5)).collect();
+        let env = EnvironmentMap::new(8, 4, pixels);
+        // Midpoint rule over the sphere in (phi, theta).
+        let n = 400;
+        let mut integral = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let phi = 2.0 * PI * (i as f64 + 0.5) / n as f64;
+                let theta = PI * (j as f64 + 0.5) / n as f64;
+                let (sin_theta, cos_theta) = theta.sin_cos();
+                let d = Vec3::new(phi.cos() * sin_theta, cos_theta, phi.sin() * sin_theta);
+                integral += env.pdf_direction(d) * sin_theta * 2.0 * PI * PI / (n * n) as f64;
+            }
+        }
+        assert!((integral - 1.0).abs() < 1e-2, "{}", integral);
+    }
+
+    #[test]
+    fn test_open_hdr() {
+        let path = std::env::temp_dir().join("raytracer_environment_map_test.hdr");
+        let data = vec![Rgb([0.5_f32, 1.0, 8.0]); 4 * 2];
+        HdrEncoder::new(File::create(&path).unwrap())
+            .encode(&data, 4, 2)
+            .unwrap();
+        let env = EnvironmentMap::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((env.get_width(), env.get_height()), (4, 2));
+        assert_near(
+            env.radiance(Vec3::new(0.0, 0.0, 1.0)),
+            Vec3::new(0.5, 1.0, 8.0),
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use crate::color::Color;
+use crate::vec3::Vec3;
+
+use crate::backgrounds::background::Background;
+
+/// A vertical blend from `bottom` straight down to `top` straight up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientBackground {
+    bottom: Vec3,
+    top: Vec3,
+}
+
+impl GradientBackground {
+    #[inline(always)]
+    pub fn new(bottom: Vec3, top: Vec3) -> GradientBackground {
+        GradientBackground { bottom, top }
+    }
+}
+
+impl Default for GradientBackground {
+    /// The white to light blue sky of Ray Tracing in One Weekend.
+    fn default() -> GradientBackground {
+        GradientBackground::new(
+            Vec3::from(Color::new(255, 255, 255)),
+            Vec3::from(Color::new(128, 179, 255)),
+        )
+    }
+}
+
+impl Background for GradientBackground {
+    fn radiance(&self, direction: Vec3) -> Vec3 {
+        let t = 0.5 * (direction.unit_vector().get_y() + 1.0);
+        ((1.0 - t) * self.bottom) + (t * self.top)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod background;
+pub mod environment_map;
+pub mod gradient_background;
+pub mod preetham_sky;
+pub mod solid_background;
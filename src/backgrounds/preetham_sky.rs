@@ -0,0 +1,328 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::color::Color;
+use crate::onb::Onb;
+use crate::sampling::{uniform_cone, uniform_cone_pdf, uniform_sphere};
+use crate::vec3::Vec3;
+
+use crate::backgrounds::background::Background;
+
+/// Scales the model's kcd/m^2 so a clear midday sky comes out near 1.
+const DEFAULT_INTENSITY: f64 = 0.05;
+/// Bright enough that the sun's irradiance is a few times the sky's.
+const DEFAULT_SUN_INTENSITY: f64 = 30000.0;
+/// The sun's apparent angular radius in radians.
+const SUN_ANGULAR_RADIUS: f64 = 0.00465;
+/// How often `sample_direction` aims at the sun rather than anywhere on the sphere.
+const SUN_SAMPLE_FRACTION: f64 = 0.5;
+
+// Perez distribution coefficients A to E as linear functions of turbidity, from Preetham,
+// Shirley and Smits, "A Practical Analytic Model for Daylight" (1999).
+const PEREZ_Y: [(f64, f64); 5] = [
+    (0.1787, -1.4630),
+    (-0.3554, 0.4275),
+    (-0.0227, 5.3251),
+    (0.1206, -2.5771),
+    (-0.0670, 0.3703),
+];
+const PEREZ_X: [(f64, f64); 5] = [
+    (-0.0193, -0.2592),
+    (-0.0665, 0.0008),
+    (-0.0004, 0.2125),
+    (-0.0641, -0.8989),
+    (-0.0033, 0.0452),
+];
+const PEREZ_SMALL_Y: [(f64, f64); 5] = [
+    (-0.0167, -0.2608),
+    (-0.0950, 0.0092),
+    (-0.0079, 0.2102),
+    (-0.0441, -1.6537),
+    (-0.0109, 0.0529),
+];
+
+// Zenith chromaticity as [T^2, T, 1] * M * [theta^3, theta^2, theta, 1].
+const ZENITH_X: [[f64; 4]; 3] = [
+    [0.00166, -0.00375, 0.00209, 0.0],
+    [-0.02903, 0.06377, -0.03202, 0.00394],
+    [0.11693, -0.21196, 0.06052, 0.25886],
+];
+const ZENITH_Y: [[f64; 4]; 3] = [
+    [0.00275, -0.00610, 0.00317, 0.0],
+    [-0.04214, 0.08970, -0.04153, 0.00516],
+    [0.15346, -0.26756, 0.06670, 0.26688],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Perez {
+    coefficients: [f64; 5],
+    // Zenith value divided by the distribution at the zenith, so evaluating needs no division.
+    scale: f64,
+}
+
+impl Perez {
+    fn new(table: &[(f64, f64); 5], turbidity: f64, zenith: f64, sun_theta: f64) -> Perez {
+        let mut coefficients = [0.0; 5];
+        for (c, (slope, offset)) in coefficients.iter_mut().zip(table.iter()) {
+            *c = (slope * turbidity) + offset;
+        }
+        let mut perez = Perez {
+            coefficients,
+            scale: 1.0,
+        };
+        perez.scale = zenith / perez.distribution(0.0, sun_theta);
+        perez
+    }
+
+    // F(theta, gamma) for view zenith angle theta and angle gamma to the sun.
+    fn distribution(&self, theta: f64, gamma: f64) -> f64 {
+        let [a, b, c, d, e] = self.coefficients;
+        (1.0 + (a * (b / theta.cos().max(0.01)).exp()))
+            * (1.0 + (c * (d * gamma).exp()) + (e * gamma.cos().powi(2)))
+    }
+
+    #[inline(always)]
+    fn eval(&self, theta: f64, gamma: f64) -> f64 {
+        self.scale * self.distribution(theta, gamma)
+    }
+}
+
+fn zenith_chromaticity(m: &[[f64; 4]; 3], turbidity: f64, sun_theta: f64) -> f64 {
+    let t = [turbidity * turbidity, turbidity, 1.0];
+    let s = [sun_theta.powi(3), sun_theta.powi(2), sun_theta, 1.0];
+    (0..3)
+        .map(|i| t[i] * (0..4).map(|j| m[i][j] * s[j]).sum::<f64>())
+        .sum()
+}
+
+// CIE xyY to linear sRGB.
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> Vec3 {
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+    Vec3::new(
+        (3.2406 * big_x) - (1.5372 * luminance) - (0.4986 * big_z),
+        (-0.9689 * big_x) + (1.8758 * luminance) + (0.0415 * big_z),
+        (0.0557 * big_x) - (0.2040 * luminance) + (1.0570 * big_z),
+    )
+}
+
+// Sunlight through the atmosphere: Rayleigh and aerosol extinction at red, green and blue
+// wavelengths over the Kasten-Young air mass, as in the Preetham paper's appendix.
+fn sun_transmittance(sun_theta: f64, turbidity: f64) -> Vec3 {
+    let degrees = sun_theta.to_degrees();
+    let air_mass = 1.0 / (sun_theta.cos() + (0.50572 * (96.07995 - degrees).powf(-1.6364)));
+    let beta = (0.04608 * turbidity) - 0.04586;
+    let channel = |wavelength_um: f64| {
+        let rayleigh = 0.008735 * wavelength_um.powf(-4.08);
+        let aerosol = beta * wavelength_um.powf(-1.3);
+        (-air_mass * (rayleigh + aerosol)).exp()
+    };
+    Vec3::new(channel(0.680), channel(0.550), channel(0.440))
+}
+
+/// The Preetham clear sky with a sun disk. Below the horizon the sky is mirrored onto a ground
+/// of the given albedo.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreethamSky {
+    sun_direction: Vec3,
+    turbidity: f64,
+    ground_albedo: Vec3,
+    intensity: f64,
+    sun_radiance: Vec3,
+    perez_luminance: Perez,
+    perez_x: Perez,
+    perez_y: Perez,
+}
+
+impl PreethamSky {
+    /// `turbidity` ranges from about 2 for a very clear sky to 10 for haze. The sun should be
+    /// above the horizon, the model is not valid for twilight.
+    pub fn new(sun_direction: Vec3, turbidity: f64, ground_albedo: Color) -> PreethamSky {
+        let sun_direction = sun_direction.unit_vector();
+        let turbidity = turbidity.clamp(1.7, 10.0);
+        // Keep the sun just above the horizon where the fit still behaves.
+        let sun_theta = sun_direction
+            .get_y()
+            .clamp(-1.0, 1.0)
+            .acos()
+            .min(FRAC_PI_2 - 0.01);
+
+        let chi = ((4.0 / 9.0) - (turbidity / 120.0)) * (PI - (2.0 * sun_theta));
+        let zenith_luminance =
+            (((4.0453 * turbidity) - 4.9710) * chi.tan()) - (0.2155 * turbidity) + 2.4192;
+        let zenith_x = zenith_chromaticity(&ZENITH_X, turbidity, sun_theta);
+        let zenith_y = zenith_chromaticity(&ZENITH_Y, turbidity, sun_theta);
+
+        let sun_radiance = if sun_direction.get_y() > 0.0 {
+            DEFAULT_SUN_INTENSITY * sun_transmittance(sun_theta, turbidity)
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        };
+
+        PreethamSky {
+            sun_direction,
+            turbidity,
+            ground_albedo: Vec3::from(ground_albedo),
+            intensity: DEFAULT_INTENSITY,
+            sun_radiance,
+            perez_luminance: Perez::new(&PEREZ_Y, turbidity, zenith_luminance, sun_theta),
+            perez_x: Perez::new(&PEREZ_X, turbidity, zenith_x, sun_theta),
+            perez_y: Perez::new(&PEREZ_SMALL_Y, turbidity, zenith_y, sun_theta),
+        }
+    }
+
+    /// Places the sun `elevation` degrees above the horizon, `azimuth` degrees from +x towards
+    /// -z.
+    pub fn from_sun_angles(
+        elevation: f64,
+        azimuth: f64,
+        turbidity: f64,
+        ground_albedo: Color,
+    ) -> PreethamSky {
+        let (sin_e, cos_e) = elevation.to_radians().sin_cos();
+        let (sin_a, cos_a) = azimuth.to_radians().sin_cos();
+        PreethamSky::new(
+            Vec3::new(cos_e * cos_a, sin_e, -cos_e * sin_a),
+            turbidity,
+            ground_albedo,
+        )
+    }
+
+    /// Scales the sky, not the sun.
+    #[inline(always)]
+    pub fn with_intensity(mut self, intensity: f64) -> PreethamSky {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Scales the sun disk relative to its default brightness.
+    #[inline(always)]
+    pub fn with_sun_intensity(mut self, scale: f64) -> PreethamSky {
+        self.sun_radiance *= scale;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_sun_direction(&self) -> Vec3 {
+        self.sun_direction
+    }
+
+    #[inline(always)]
+    pub fn get_sun_radiance(&self) -> Vec3 {
+        self.sun_radiance
+    }
+
+    #[inline(always)]
+    pub fn get_turbidity(&self) -> f64 {
+        self.turbidity
+    }
+
+    /// The sky alone, without the sun disk or ground.
+    pub fn sky_radiance(&self, direction: Vec3) -> Vec3 {
+        let d = direction.unit_vector();
+        let theta = d.get_y().clamp(0.0, 1.0).acos();
+        let gamma = d.dot(&self.sun_direction).clamp(-1.0, 1.0).acos();
+        let rgb = xyy_to_rgb(
+            self.perez_x.eval(theta, gamma),
+            self.perez_y.eval(theta, gamma),
+            self.perez_luminance.eval(theta, gamma),
+        );
+        self.intensity
+            * Vec3::new(
+                rgb.get_x().max(0.0),
+                rgb.get_y().max(0.0),
+                rgb.get_z().max(0.0),
+            )
+    }
+
+    #[inline(always)]
+    fn cos_sun_radius() -> f64 {
+        SUN_ANGULAR_RADIUS.cos()
+    }
+}
+
+impl Background for PreethamSky {
+    fn radiance(&self, direction: Vec3) -> Vec3 {
+        let d = direction.unit_vector();
+        if d.get_y() < 0.0 {
+            let horizon = Vec3::new(d.get_x(), 0.0, d.get_z());
+            return self.ground_albedo * self.sky_radiance(horizon);
+        }
+        let sky = self.sky_radiance(d);
+        if d.dot(&self.sun_direction) >= PreethamSky::cos_sun_radius() {
+            sky + self.sun_radiance
+        } else {
+            sky
+        }
+    }
+
+    // Half the samples go to the sun, which is far too small to find by chance.
+    fn sample_direction(&self, u: (f64, f64)) -> Option<(Vec3, f64)> {
+        let direction = if u.0 < SUN_SAMPLE_FRACTION {
+            let remapped = (u.0 / SUN_SAMPLE_FRACTION, u.1);
+            Onb::from_w(self.sun_direction)
+                .local(uniform_cone(remapped, PreethamSky::cos_sun_radius()))
+        } else {
+            let remapped = (
+                (u.0 - SUN_SAMPLE_FRACTION) / (1.0 - SUN_SAMPLE_FRACTION),
+                u.1,
+            );
+            uniform_sphere(remapped)
+        };
+        Some((direction, self.pdf_direction(direction)))
+    }
+
+    fn pdf_direction(&self, direction: Vec3) -> f64 {
+        let sphere = (1.0 - SUN_SAMPLE_FRACTION) / (4.0 * PI);
+        let cos_max = PreethamSky::cos_sun_radius();
+        if direction.unit_vector().dot(&self.sun_direction) >= cos_max {
+            sphere + (SUN_SAMPLE_FRACTION * uniform_cone_pdf(cos_max))
+        } else {
+            sphere
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sky_is_blue_and_brightens_near_sun() {
+        let sky = PreethamSky::from_sun_angles(45.0, 0.0, 3.0, Color::new(76, 76, 76));
+        let zenith = sky.radiance(Vec3::new(0.0, 1.0, 0.0));
+        assert!(zenith.get_z() > zenith.get_x());
+        assert!(zenith.get_y() > 0.1 && zenith.get_y() < 2.0, "{:?}", zenith);
+
+        // Near the sun but outside the disk is brighter than the opposite side of the sky.
+        let sun = sky.get_sun_direction();
+        let near_sun = (sun + Vec3::new(0.0, 0.1, 0.0)).unit_vector();
+        let away = Vec3::new(-sun.get_x(), sun.get_y(), -sun.get_z());
+        assert!(sky.radiance(near_sun).get_y() > sky.radiance(away).get_y());
+
+        // The disk itself is far brighter and reddened by the atmosphere.
+        let disk = sky.radiance(sun);
+        assert!(disk.get_y() > 1000.0 * zenith.get_y());
+        assert!(sky.get_sun_radiance().get_x() > sky.get_sun_radiance().get_z());
+
+        let ground = sky.radiance(Vec3::new(0.3, -1.0, 0.0));
+        assert!(ground.get_y() < sky.radiance(Vec3::new(0.3, 0.0, 0.0)).get_y());
+    }
+
+    #[test]
+    fn test_sampling_finds_the_sun() {
+        let sky = PreethamSky::from_sun_angles(30.0, 120.0, 2.5, Color::new(50, 50, 50));
+        let cos_max = PreethamSky::cos_sun_radius();
+        let mut in_sun = 0;
+        for i in 0..20 {
+            for j in 0..20 {
+                let u = (i as f64 / 20.0, j as f64 / 20.0);
+                let (d, pdf) = sky.sample_direction(u).unwrap();
+                assert!((sky.pdf_direction(d) - pdf).abs() < 1e-9 * pdf);
+                if d.dot(&sky.get_sun_direction()) >= cos_max {
+                    in_sun += 1;
+                }
+            }
+        }
+        assert!(in_sun >= 200);
+    }
+}
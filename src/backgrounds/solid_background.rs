@@ -0,0 +1,34 @@
+use crate::color::Color;
+use crate::vec3::Vec3;
+
+use crate::backgrounds::background::Background;
+
+/// The same radiance in every direction, black for scenes lit only by their own lights.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolidBackground {
+    radiance: Vec3,
+}
+
+impl SolidBackground {
+    #[inline(always)]
+    pub fn new(radiance: Vec3) -> SolidBackground {
+        SolidBackground { radiance }
+    }
+
+    #[inline(always)]
+    pub fn black() -> SolidBackground {
+        SolidBackground::new(Vec3::new(0.0, 0.0, 0.0))
+    }
+
+    #[inline(always)]
+    pub fn from_color(color: Color, intensity: f64) -> SolidBackground {
+        SolidBackground::new(intensity * Vec3::from(color))
+    }
+}
+
+impl Background for SolidBackground {
+    #[inline(always)]
+    fn radiance(&self, _direction: Vec3) -> Vec3 {
+        self.radiance
+    }
+}
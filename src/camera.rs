@@ -17,6 +17,7 @@ pub struct Camera {
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         look_from: Point3,
         look_at: Point3,
@@ -57,6 +58,12 @@ impl Camera {
         }
     }
 
+    /// The direction the camera looks in.
+    #[inline(always)]
+    pub fn get_forward(&self) -> Vec3 {
+        -self.w
+    }
+
     pub fn get_ray(&self, s: f64, t: f64) -> Ray {
         let rd = self.lens_radius * Vec3::random_in_unit_disk();
         let offset = (self.u * rd.get_x()) + (self.v * rd.get_y());
@@ -46,7 +46,8 @@ impl TryFrom<Vec3> for Color {
         let vx = v.get_x();
         let vy = v.get_y();
         let vz = v.get_z();
-        if vx >= 0.0 && vx <= 1.0 && vy >= 0.0 && vy <= 1.0 && vz >= 0.0 && vz <= 1.0 {
+        let unit = 0.0..=1.0;
+        if unit.contains(&vx) && unit.contains(&vy) && unit.contains(&vz) {
             Ok(Color::new(
                 (vx * 255.999) as u8,
                 (vy * 255.999) as u8,
@@ -58,6 +59,61 @@ impl TryFrom<Vec3> for Color {
     }
 }
 
+/// Linear RGB radiance. Unlike `Color` it has no upper limit, it is only squeezed into 8 bits
+/// when an image is written out.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinearRgb {
+    red: f32,
+    green: f32,
+    blue: f32,
+}
+
+impl LinearRgb {
+    #[inline(always)]
+    pub fn new(red: f32, green: f32, blue: f32) -> LinearRgb {
+        LinearRgb { red, green, blue }
+    }
+
+    #[inline(always)]
+    pub fn get_red(&self) -> f32 {
+        self.red
+    }
+
+    #[inline(always)]
+    pub fn get_green(&self) -> f32 {
+        self.green
+    }
+
+    #[inline(always)]
+    pub fn get_blue(&self) -> f32 {
+        self.blue
+    }
+
+    /// Applies `f` to each channel.
+    #[inline(always)]
+    pub fn map<F: Fn(f32) -> f32>(&self, f: F) -> LinearRgb {
+        LinearRgb::new(f(self.red), f(self.green), f(self.blue))
+    }
+
+    /// Quantizes to 8 bits, clamping each channel to `[0, 1]` first.
+    #[inline(always)]
+    pub fn to_color(&self) -> Color {
+        let quantize = |c: f32| (c.clamp(0.0, 1.0) * 255.999) as u8;
+        Color::new(
+            quantize(self.red),
+            quantize(self.green),
+            quantize(self.blue),
+        )
+    }
+}
+
+impl From<Vec3> for LinearRgb {
+    #[inline(always)]
+    fn from(v: Vec3) -> LinearRgb {
+        LinearRgb::new(v.get_x() as f32, v.get_y() as f32, v.get_z() as f32)
+    }
+}
+
 impl From<Color> for [u8; 3] {
     #[inline(always)]
     fn from(c: Color) -> [u8; 3] {
@@ -98,4 +154,11 @@ mod tests {
         let v = Vec3::new(1.2, 45.5, 23.4);
         assert_eq!(Color::try_from(v), Err(()));
     }
+
+    #[test]
+    fn test_linear_rgb_clamps_when_quantized() {
+        let c = LinearRgb::from(Vec3::new(35.0 / 255.0, 45.5, -0.5));
+        assert_eq!(c.get_green(), 45.5);
+        assert_eq!(c.to_color(), Color::new(35, 255, 0));
+    }
 }
@@ -0,0 +1,357 @@
+use rayon::prelude::*;
+
+use crate::aov::{AovBuffer, AovPixel};
+use crate::film::Film;
+use crate::vec3::Vec3;
+
+// The B3 spline the à-trous filter spreads over a 5 by 5 footprint.
+const KERNEL: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+// A small Gaussian that steadies the variance estimates before they are used.
+const VARIANCE_KERNEL: [f64; 3] = [0.25, 0.5, 0.25];
+
+/// An edge-avoiding à-trous wavelet filter after Dammertz et al., with the noise-aware color
+/// weights of SVGF. Each pass blurs with a 5 by 5 kernel whose taps are twice as far apart as
+/// in the previous one. A tap counts for less the further its normal and depth are from the
+/// center, and the further its luminance is, measured against how noisy the center is, so
+/// edges stay sharp while noise is averaged away. The lighting is filtered apart from the
+/// albedo, keeping texture detail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Denoiser {
+    iterations: usize,
+    luminance_sigma: f64,
+    normal_sigma: f64,
+    depth_sigma: f64,
+}
+
+impl Denoiser {
+    #[inline(always)]
+    pub fn new() -> Denoiser {
+        Denoiser {
+            iterations: 5,
+            luminance_sigma: 4.0,
+            normal_sigma: 0.3,
+            depth_sigma: 0.05,
+        }
+    }
+
+    /// The number of passes, each doubling the radius, so five cover 62 pixels across.
+    #[inline(always)]
+    pub fn with_iterations(mut self, iterations: usize) -> Denoiser {
+        self.iterations = iterations;
+        self
+    }
+
+    /// How many standard deviations of noise two luminances may be apart before they stop
+    /// being averaged.
+    #[inline(always)]
+    pub fn with_luminance_sigma(mut self, sigma: f64) -> Denoiser {
+        self.luminance_sigma = sigma;
+        self
+    }
+
+    /// How far apart two normals may be before they stop being averaged.
+    #[inline(always)]
+    pub fn with_normal_sigma(mut self, sigma: f64) -> Denoiser {
+        self.normal_sigma = sigma;
+        self
+    }
+
+    /// How different two depths may be, relative to the depth at the center, before they stop
+    /// being averaged.
+    #[inline(always)]
+    pub fn with_depth_sigma(mut self, sigma: f64) -> Denoiser {
+        self.depth_sigma = sigma;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_iterations(&self) -> usize {
+        self.iterations
+    }
+
+    #[inline(always)]
+    pub fn get_luminance_sigma(&self) -> f64 {
+        self.luminance_sigma
+    }
+
+    #[inline(always)]
+    pub fn get_normal_sigma(&self) -> f64 {
+        self.normal_sigma
+    }
+
+    #[inline(always)]
+    pub fn get_depth_sigma(&self) -> f64 {
+        self.depth_sigma
+    }
+
+    /// A filtered copy of `film`, guided by `aovs` recorded for the same image. Pixels need
+    /// at least two samples for their noise to be estimated, those with fewer are only
+    /// averaged with identical neighbours.
+    pub fn denoise(&self, film: &Film, aovs: &AovBuffer) -> Film {
+        let width = film.get_width();
+        let height = film.get_height();
+        assert!(
+            aovs.get_width() == width && aovs.get_height() == height,
+            "AOVs do not match the film"
+        );
+        let guides = aovs.get_pixels();
+
+        // Lighting alone, without the surface color, where there is one.
+        let albedo: Vec<Vec3> = guides.iter().map(demodulation).collect();
+        let mut current: Vec<(Vec3, f64)> = film
+            .get_pixels()
+            .iter()
+            .zip(&albedo)
+            .map(|(p, a)| {
+                let color = divide(Vec3::from(p.get_radiance()), *a);
+                (color, p.get_variance() / (a.luminance() * a.luminance()))
+            })
+            .collect();
+
+        let mut next = current.clone();
+        for i in 0..self.iterations {
+            let step = 1 << i;
+            let variance = blurred_variance(&current, width, height);
+            next.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+                for (x, out) in row.iter_mut().enumerate() {
+                    *out =
+                        self.filter_pixel(&current, &variance, guides, width, height, x, y, step);
+                }
+            });
+            std::mem::swap(&mut current, &mut next);
+        }
+
+        let mut output = Film::new(width, height);
+        for (i, ((c, _), a)) in current.iter().zip(&albedo).enumerate() {
+            output.add_sample(i % width, i / width, *c * *a);
+        }
+        output
+    }
+
+    // One tap of a pass: the weighted color around `(x, y)` and the variance left in it.
+    #[allow(clippy::too_many_arguments)]
+    fn filter_pixel(
+        &self,
+        pixels: &[(Vec3, f64)],
+        variance: &[f64],
+        guides: &[AovPixel],
+        width: usize,
+        height: usize,
+        x: usize,
+        y: usize,
+        step: usize,
+    ) -> (Vec3, f64) {
+        let center = (y * width) + x;
+        let luminance = pixels[center].0.luminance();
+        let luminance_scale = (self.luminance_sigma * variance[center].sqrt()) + 1e-10;
+        let guide = &guides[center];
+        let mut color_sum = Vec3::new(0.0, 0.0, 0.0);
+        let mut variance_sum = 0.0;
+        let mut total_weight = 0.0;
+        for (j, ky) in KERNEL.iter().enumerate() {
+            let qy = y as isize + ((j as isize - 2) * step as isize);
+            if qy < 0 || qy >= height as isize {
+                continue;
+            }
+            for (i, kx) in KERNEL.iter().enumerate() {
+                let qx = x as isize + ((i as isize - 2) * step as isize);
+                if qx < 0 || qx >= width as isize {
+                    continue;
+                }
+                let q = (qy as usize * width) + qx as usize;
+                let (color, color_variance) = pixels[q];
+                let other = &guides[q];
+                let luminance_distance = (color.luminance() - luminance).abs();
+                let normal_distance = (other.get_normal() - guide.get_normal()).length_squared();
+                let weight = kx
+                    * ky
+                    * (-luminance_distance / luminance_scale).exp()
+                    * (-normal_distance / (self.normal_sigma * self.normal_sigma)).exp()
+                    * self.depth_weight(guide.get_depth(), other.get_depth(), step);
+                color_sum += weight * color;
+                variance_sum += weight * weight * color_variance;
+                total_weight += weight;
+            }
+        }
+        // The center always has a positive weight of its own.
+        (
+            color_sum / total_weight,
+            variance_sum / (total_weight * total_weight),
+        )
+    }
+
+    // Depth differences grow with the distance between taps, so they are measured per pixel.
+    fn depth_weight(&self, center: f64, other: f64, step: usize) -> f64 {
+        match (center.is_finite(), other.is_finite()) {
+            (true, true) => {
+                let relative = (center - other).abs() / (center.max(1e-6) * step as f64);
+                (-(relative * relative) / (self.depth_sigma * self.depth_sigma)).exp()
+            }
+            (false, false) => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Denoiser {
+        Denoiser::new()
+    }
+}
+
+fn blurred_variance(pixels: &[(Vec3, f64)], width: usize, height: usize) -> Vec<f64> {
+    (0..pixels.len())
+        .map(|center| {
+            let (x, y) = ((center % width) as isize, (center / width) as isize);
+            let mut sum = 0.0;
+            let mut total_weight = 0.0;
+            for (j, ky) in VARIANCE_KERNEL.iter().enumerate() {
+                for (i, kx) in VARIANCE_KERNEL.iter().enumerate() {
+                    let (qx, qy) = (x + i as isize - 1, y + j as isize - 1);
+                    if qx >= 0 && qx < width as isize && qy >= 0 && qy < height as isize {
+                        sum += kx * ky * pixels[(qy as usize * width) + qx as usize].1;
+                        total_weight += kx * ky;
+                    }
+                }
+            }
+            sum / total_weight
+        })
+        .collect()
+}
+
+// The albedo to divide out of a pixel, one in channels too dark to divide by.
+fn demodulation(guide: &AovPixel) -> Vec3 {
+    let albedo = guide.get_albedo();
+    let channel = |a: f64| if a < 0.01 { 1.0 } else { a };
+    Vec3::new(
+        channel(albedo.get_x()),
+        channel(albedo.get_y()),
+        channel(albedo.get_z()),
+    )
+}
+
+#[inline(always)]
+fn divide(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(
+        a.get_x() / b.get_x(),
+        a.get_y() / b.get_y(),
+        a.get_z() / b.get_z(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::aov::AovSample;
+    use crate::color::Color;
+    use crate::objects::aarect::XYRect;
+    use crate::objects::hittable::Hittable;
+    use crate::objects::hittable_list::HittableList;
+    use crate::objects::lambertian::Lambertian;
+    use crate::ray::Ray;
+    use crate::vec3::Point3;
+
+    const SIZE: usize = 32;
+
+    // A grey wall on the left half of the image and a white one, further away and facing
+    // another way, on the right, seen straight on with one ray per pixel.
+    fn aovs() -> AovBuffer {
+        let grey = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let white = Arc::new(Lambertian::new(Color::new(255, 255, 255)));
+        let mut world = HittableList::new();
+        world.add(Arc::new(XYRect::new(-1.0, 0.0, -1.0, 1.0, 0.0, grey)));
+        world.add(Arc::new(XYRect::new(0.0, 1.0, -1.0, 1.0, -1.0, white)));
+        let mut aovs = AovBuffer::new(SIZE, SIZE);
+        for (i, pixel) in aovs.get_pixels_mut().iter_mut().enumerate() {
+            let (x, y) = (i % SIZE, i / SIZE);
+            let origin = Point3::new(
+                ((x as f64 + 0.5) / SIZE as f64 * 2.0) - 1.0,
+                1.0 - ((y as f64 + 0.5) / SIZE as f64 * 2.0),
+                5.0,
+            );
+            let r = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let rec = world.hit(&r, 0.001, f64::INFINITY).unwrap();
+            let rec = if x < SIZE / 2 {
+                rec
+            } else {
+                rec.with_shading_normal(Vec3::new(1.0, 0.0, 0.0))
+            };
+            let sample = AovSample::from_hit(&r, &rec, Vec3::new(0.0, 0.0, -1.0));
+            pixel.add_sample(Some(&sample));
+        }
+        aovs
+    }
+
+    // Both walls lit evenly, with noise on top.
+    fn noisy_film(rng: &mut StdRng) -> Film {
+        let mut film = Film::new(SIZE, SIZE);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let level = if x < SIZE / 2 { 0.2 } else { 0.8 };
+                for _ in 0..8 {
+                    let value = level * rng.gen_range(0.0..2.0);
+                    film.add_sample(x, y, Vec3::new(value, value, value));
+                }
+            }
+        }
+        film
+    }
+
+    fn stats(film: &Film, columns: std::ops::Range<usize>) -> (f32, f32) {
+        let values: Vec<f32> = (0..SIZE)
+            .flat_map(|y| columns.clone().map(move |x| (x, y)))
+            .map(|(x, y)| film.get_pixel(x, y).get_green())
+            .collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance =
+            values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+        (mean, variance)
+    }
+
+    #[test]
+    fn test_removes_noise_but_keeps_edges() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let film = noisy_film(&mut rng);
+        let denoised = Denoiser::new().denoise(&film, &aovs());
+
+        for (columns, level) in &[(0..(SIZE / 2), 0.2), ((SIZE / 2)..SIZE, 0.8)] {
+            let (noisy_mean, noisy_variance) = stats(&film, columns.clone());
+            let (mean, variance) = stats(&denoised, columns.clone());
+            assert!(
+                variance < noisy_variance / 10.0,
+                "{} {}",
+                variance,
+                noisy_variance
+            );
+            assert!((mean - noisy_mean).abs() < 0.05 * level);
+        }
+        // Right next to the edge neither side takes on the other's brightness.
+        let left = (0..SIZE).map(|y| denoised.get_pixel(SIZE / 2 - 1, y).get_green());
+        let right = (0..SIZE).map(|y| denoised.get_pixel(SIZE / 2, y).get_green());
+        assert!(left.sum::<f32>() / (SIZE as f32) < 0.3);
+        assert!(right.sum::<f32>() / (SIZE as f32) > 0.6);
+    }
+
+    #[test]
+    fn test_no_iterations_leaves_film_alone() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let film = noisy_film(&mut rng);
+        let denoised = Denoiser::new().with_iterations(0).denoise(&film, &aovs());
+        for (a, b) in film.to_linear().iter().zip(denoised.to_linear()) {
+            for (x, y) in [
+                (a.get_red(), b.get_red()),
+                (a.get_green(), b.get_green()),
+                (a.get_blue(), b.get_blue()),
+            ] {
+                assert!((x - y).abs() < 1e-5);
+            }
+        }
+    }
+}
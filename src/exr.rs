@@ -0,0 +1,144 @@
+use std::io::{Result, Write};
+
+/// Writes an uncompressed, single part, scanline OpenEXR image with 32-bit float channels.
+/// Each channel is a full image in row order from the top down, named like `R` or, for extra
+/// layers, `albedo.R`. Channels are stored sorted by name, as the format requires.
+pub fn write_exr<W: Write>(
+    mut writer: W,
+    width: usize,
+    height: usize,
+    channels: &[(&str, &[f32])],
+) -> Result<()> {
+    for (name, values) in channels {
+        assert_eq!(
+            values.len(),
+            width * height,
+            "channel {} does not match size",
+            name
+        );
+    }
+    let mut channels = channels.to_vec();
+    channels.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+    header.extend_from_slice(&2_u32.to_le_bytes());
+
+    let mut chlist = Vec::new();
+    for (name, _) in &channels {
+        chlist.extend_from_slice(name.as_bytes());
+        chlist.push(0);
+        // FLOAT pixels, not perceptually linear, three reserved bytes, no subsampling.
+        chlist.extend_from_slice(&2_i32.to_le_bytes());
+        chlist.extend_from_slice(&[0, 0, 0, 0]);
+        chlist.extend_from_slice(&1_i32.to_le_bytes());
+        chlist.extend_from_slice(&1_i32.to_le_bytes());
+    }
+    chlist.push(0);
+    attribute(&mut header, "channels", "chlist", &chlist);
+    attribute(&mut header, "compression", "compression", &[0]);
+
+    let mut window = Vec::new();
+    for v in &[0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&v.to_le_bytes());
+    }
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1_f32.to_le_bytes(),
+    );
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1_f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    // One scanline per chunk, each a y coordinate and byte count followed by the channels.
+    let line_size = channels.len() * width * 4;
+    let chunk_size = 8 + line_size;
+    let first_chunk = header.len() + (height * 8);
+    for y in 0..height {
+        let offset = (first_chunk + (y * chunk_size)) as u64;
+        header.extend_from_slice(&offset.to_le_bytes());
+    }
+    writer.write_all(&header)?;
+
+    let mut line = Vec::with_capacity(chunk_size);
+    for y in 0..height {
+        line.clear();
+        line.extend_from_slice(&(y as i32).to_le_bytes());
+        line.extend_from_slice(&(line_size as i32).to_le_bytes());
+        for (_, values) in &channels {
+            for v in &values[(y * width)..((y + 1) * width)] {
+                line.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        writer.write_all(&line)?;
+    }
+    Ok(())
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u64(bytes: &[u8], at: usize) -> u64 {
+        let mut b = [0; 8];
+        b.copy_from_slice(&bytes[at..(at + 8)]);
+        u64::from_le_bytes(b)
+    }
+
+    fn read_f32(bytes: &[u8], at: usize) -> f32 {
+        let mut b = [0; 4];
+        b.copy_from_slice(&bytes[at..(at + 4)]);
+        f32::from_le_bytes(b)
+    }
+
+    #[test]
+    fn test_layout() {
+        let red = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let green = [0.5; 6];
+        let blue = [-1.0, 0.0, 1e6, 0.0, 0.0, 0.25];
+        let mut output = Vec::new();
+        write_exr(
+            &mut output,
+            3,
+            2,
+            &[("R", &red), ("G", &green), ("B", &blue)],
+        )
+        .unwrap();
+
+        assert_eq!(output[..4], [0x76, 0x2f, 0x31, 0x01]);
+        let header = String::from_utf8_lossy(&output);
+        assert!(header.contains("channels\0chlist\0"));
+        assert!(header.contains("B\0"));
+
+        // The offset table starts right after the header and points at each scanline.
+        let line_size = 3 * 3 * 4;
+        let table = output.len() - (2 * (8 + line_size)) - 16;
+        let second = read_u64(&output, table + 8) as usize;
+        assert_eq!(second, output.len() - (8 + line_size));
+        assert_eq!(read_f32(&output, second - 4), 3.0);
+        // Scanline y = 1 holds B, G then R, three values each.
+        assert_eq!(output[second..(second + 4)], 1_i32.to_le_bytes());
+        assert_eq!(read_f32(&output, second + 8 + 8), 0.25);
+        assert_eq!(read_f32(&output, second + 8 + 12), 0.5);
+        assert_eq!(read_f32(&output, second + 8 + 24), 4.0);
+    }
+}
@@ -0,0 +1,298 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::codecs::hdr::HdrEncoder;
+use image::{save_buffer, ColorType, ImageResult, Rgb};
+
+use crate::color::LinearRgb;
+use crate::exr::write_exr;
+use crate::pfm::write_pfm;
+use crate::tone_mapping::ToneMapper;
+use crate::vec3::Vec3;
+
+/// The radiance samples that landed in one pixel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FilmPixel {
+    sum: Vec3,
+    luminance_squares: f64,
+    count: u32,
+}
+
+impl FilmPixel {
+    /// Adds one radiance sample. Samples that are not finite are dropped, a single NaN would
+    /// otherwise ruin the whole pixel.
+    #[inline(always)]
+    pub fn add_sample(&mut self, radiance: Vec3) {
+        if radiance.get_x().is_finite()
+            && radiance.get_y().is_finite()
+            && radiance.get_z().is_finite()
+        {
+            self.sum += radiance;
+            self.luminance_squares += radiance.luminance() * radiance.luminance();
+            self.count += 1;
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_sample_count(&self) -> u32 {
+        self.count
+    }
+
+    /// How far the luminance of `get_radiance` is likely to be off, as the variance of the
+    /// mean of the samples. Zero until there are two samples to estimate it from.
+    #[inline(always)]
+    pub fn get_variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+        let n = self.count as f64;
+        let mean = self.sum.luminance() / n;
+        let sample_variance = ((self.luminance_squares / n) - (mean * mean)) * n / (n - 1.0);
+        sample_variance.max(0.0) / n
+    }
+
+    /// The mean of the samples so far, black before there are any.
+    #[inline(always)]
+    pub fn get_radiance(&self) -> LinearRgb {
+        if self.count == 0 {
+            LinearRgb::default()
+        } else {
+            LinearRgb::from(self.sum / self.count as f64)
+        }
+    }
+}
+
+impl Default for FilmPixel {
+    fn default() -> FilmPixel {
+        FilmPixel {
+            sum: Vec3::new(0.0, 0.0, 0.0),
+            luminance_squares: 0.0,
+            count: 0,
+        }
+    }
+}
+
+/// A floating point image that samples are accumulated into. Pixels are stored in rows from
+/// the top of the image down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Film {
+    width: usize,
+    height: usize,
+    pixels: Vec<FilmPixel>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Film {
+        Film {
+            width,
+            height,
+            pixels: vec![FilmPixel::default(); width * height],
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    #[inline(always)]
+    pub fn add_sample(&mut self, x: usize, y: usize, radiance: Vec3) {
+        self.pixels[(y * self.width) + x].add_sample(radiance);
+    }
+
+    #[inline(always)]
+    pub fn get_pixel(&self, x: usize, y: usize) -> LinearRgb {
+        self.pixels[(y * self.width) + x].get_radiance()
+    }
+
+    #[inline(always)]
+    pub fn get_pixels(&self) -> &[FilmPixel] {
+        &self.pixels
+    }
+
+    /// The pixels in row order, for filling rows in parallel.
+    #[inline(always)]
+    pub fn get_pixels_mut(&mut self) -> &mut [FilmPixel] {
+        &mut self.pixels
+    }
+
+    /// Every pixel's radiance in row order.
+    pub fn to_linear(&self) -> Vec<LinearRgb> {
+        self.pixels.iter().map(|p| p.get_radiance()).collect()
+    }
+
+    /// Writes the image in the format given by the extension of `path`. OpenEXR (`.exr`),
+    /// Radiance (`.hdr`) and PFM (`.pfm`) keep the linear radiance as it is, anything else is
+    /// passed through `tone_mapper` into 8 bits.
+    pub fn save<P: AsRef<Path>>(&self, path: P, tone_mapper: &ToneMapper) -> ImageResult<()> {
+        save_linear(path, self.width, self.height, &self.to_linear(), |c| {
+            tone_mapper.to_display(c)
+        })
+    }
+
+    /// Writes an OpenEXR file with the radiance as `R`, `G` and `B` followed by `layers`, full
+    /// size channels named like `albedo.R`.
+    pub fn save_exr<P: AsRef<Path>>(
+        &self,
+        path: P,
+        layers: &[(String, Vec<f32>)],
+    ) -> ImageResult<()> {
+        save_exr_layers(path, self.width, self.height, &self.to_linear(), layers)
+    }
+}
+
+/// Writes linear pixels, in row order from the top down, in the format given by the extension
+/// of `path`. OpenEXR (`.exr`), Radiance (`.hdr`) and PFM (`.pfm`) files get the values as they
+/// are, Radiance clipping anything negative. Any other format is 8-bit and gets each pixel
+/// passed through `encode` first, which should map what can be displayed to `[0, 1]`.
+pub fn save_linear<P, F>(
+    path: P,
+    width: usize,
+    height: usize,
+    pixels: &[LinearRgb],
+    encode: F,
+) -> ImageResult<()>
+where
+    P: AsRef<Path>,
+    F: Fn(LinearRgb) -> LinearRgb,
+{
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("exr") => save_exr_layers(path, width, height, pixels, &[])?,
+        Some("hdr") => {
+            let rgb: Vec<Rgb<f32>> = pixels
+                .iter()
+                .map(|p| p.map(|x| x.max(0.0)))
+                .map(|p| Rgb([p.get_red(), p.get_green(), p.get_blue()]))
+                .collect();
+            HdrEncoder::new(BufWriter::new(File::create(path)?)).encode(&rgb, width, height)?;
+        }
+        Some("pfm") => {
+            write_pfm(BufWriter::new(File::create(path)?), width, height, pixels)?;
+        }
+        _ => {
+            let rgb = to_rgb8(pixels, encode);
+            save_buffer(path, &rgb, width as u32, height as u32, ColorType::Rgb8)?;
+        }
+    }
+    Ok(())
+}
+
+// Interleaved 8-bit RGB after passing each pixel through `encode`, anything it leaves outside
+// `[0, 1]` is clamped.
+fn to_rgb8<F: Fn(LinearRgb) -> LinearRgb>(pixels: &[LinearRgb], encode: F) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|p| {
+            let rgb: [u8; 3] = encode(*p).to_color().into();
+            rgb
+        })
+        .collect()
+}
+
+// Writes the pixels as `R`, `G` and `B` followed by any extra `layers`.
+fn save_exr_layers<P: AsRef<Path>>(
+    path: P,
+    width: usize,
+    height: usize,
+    pixels: &[LinearRgb],
+    layers: &[(String, Vec<f32>)],
+) -> ImageResult<()> {
+    let channel = |f: fn(&LinearRgb) -> f32| pixels.iter().map(f).collect::<Vec<_>>();
+    let red = channel(LinearRgb::get_red);
+    let green = channel(LinearRgb::get_green);
+    let blue = channel(LinearRgb::get_blue);
+    let mut channels: Vec<(&str, &[f32])> = vec![("R", &red), ("G", &green), ("B", &blue)];
+    channels.extend(
+        layers
+            .iter()
+            .map(|(name, values)| (name.as_str(), values.as_slice())),
+    );
+    write_exr(
+        BufWriter::new(File::create(path)?),
+        width,
+        height,
+        &channels,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulates_unbounded_samples() {
+        let mut film = Film::new(2, 1);
+        film.add_sample(1, 0, Vec3::new(30.0, 0.0, 1.0));
+        film.add_sample(1, 0, Vec3::new(10.0, 0.0, 0.0));
+        film.add_sample(1, 0, Vec3::new(f64::NAN, 0.0, 0.0));
+        film.add_sample(1, 0, Vec3::new(f64::INFINITY, 0.0, 0.0));
+        assert_eq!(film.get_pixels()[1].get_sample_count(), 2);
+        assert_eq!(film.get_pixel(1, 0), LinearRgb::new(20.0, 0.0, 0.5));
+        assert_eq!(film.get_pixel(0, 0), LinearRgb::default());
+
+        let pixels = film.to_linear();
+        assert_eq!(to_rgb8(&pixels, |c| c), vec![0, 0, 0, 255, 0, 127]);
+        assert_eq!(to_rgb8(&pixels, |c| c.map(|x| x / 40.0))[3..], [127, 0, 3]);
+    }
+
+    #[test]
+    fn test_variance_of_the_mean() {
+        let mut pixel = FilmPixel::default();
+        pixel.add_sample(Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(pixel.get_variance(), 0.0);
+        pixel.add_sample(Vec3::new(3.0, 3.0, 3.0));
+        assert!((pixel.get_variance() - 1.0).abs() < 1e-9);
+        for _ in 0..2 {
+            pixel.add_sample(Vec3::new(1.0, 1.0, 1.0));
+            pixel.add_sample(Vec3::new(3.0, 3.0, 3.0));
+        }
+        // The same spread over more samples, so the mean is more certain.
+        assert!((pixel.get_variance() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_save_by_extension() {
+        use image::codecs::hdr::HdrDecoder;
+        use std::io::BufReader;
+
+        let mut film = Film::new(2, 2);
+        film.add_sample(0, 0, Vec3::new(8.0, 1.0, 0.5));
+        film.add_sample(1, 1, Vec3::new(0.25, 0.25, 0.25));
+        let dir = std::env::temp_dir();
+        let tone_mapper = ToneMapper::default();
+
+        // Radiance files keep values above one.
+        let path = dir.join("raytracer_film_test.hdr");
+        film.save(&path, &tone_mapper).unwrap();
+        let decoder = HdrDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let hdr = decoder.read_image_hdr().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hdr[0], Rgb([8.0, 1.0, 0.5]));
+
+        let path = dir.join("raytracer_film_test.png");
+        film.save(&path, &tone_mapper).unwrap();
+        let png = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(png.get_pixel(0, 0), &Rgb([255, 255, 188]));
+
+        for name in &["raytracer_film_test.exr", "raytracer_film_test.PFM"] {
+            let path = dir.join(name);
+            film.save(&path, &tone_mapper).unwrap();
+            let size = std::fs::metadata(&path).unwrap().len();
+            std::fs::remove_file(&path).unwrap();
+            assert!(size > 4 * 12);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use crate::objects::hittable::HitRecord;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vec3::Vec3;
+
+/// Turns camera rays into radiance estimates.
+pub trait Integrator {
+    /// An estimate of the radiance arriving along `r`, travelling the opposite way.
+    fn li(&self, r: &Ray, scene: &Scene) -> Vec3;
+
+    /// `li` together with the first surface `r` hits, if any, for recording AOVs. Integrators
+    /// that find it anyway should return theirs instead of tracing `r` twice.
+    fn li_with_first_hit(&self, r: &Ray, scene: &Scene) -> (Vec3, Option<HitRecord>) {
+        let first_hit = scene.get_world().hit(r, 0.001, f64::INFINITY);
+        (self.li(r, scene), first_hit)
+    }
+}
@@ -0,0 +1,2 @@
+pub mod integrator;
+pub mod path_tracer;
@@ -0,0 +1,307 @@
+use rand::random;
+
+use crate::objects::hittable::{HitRecord, Hittable};
+use crate::objects::material::ScatterRecord;
+use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::vec3::Vec3;
+
+use crate::integrators::integrator::Integrator;
+
+/// How multiple importance sampling splits a path between two strategies that could both have
+/// produced it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Weights in proportion to the densities.
+    Balance,
+    /// Weights in proportion to the squared densities, favoring the better strategy more.
+    #[default]
+    Power,
+}
+
+impl Heuristic {
+    /// The weight of a sample drawn with density `pdf` that the other strategy would have drawn
+    /// with density `other_pdf`. The weights of both strategies add up to one.
+    pub fn weight(&self, pdf: f64, other_pdf: f64) -> f64 {
+        let (f, g) = match self {
+            Heuristic::Balance => (pdf, other_pdf),
+            Heuristic::Power => (pdf * pdf, other_pdf * other_pdf),
+        };
+        if f.is_infinite() {
+            1.0
+        } else if f + g <= 0.0 {
+            0.0
+        } else {
+            f / (f + g)
+        }
+    }
+}
+
+/// A unidirectional path tracer. At every diffuse bounce it samples the lights as well as the
+/// material and combines both with multiple importance sampling. Paths end at random through
+/// Russian roulette, so there is no fixed depth limit to bias the result.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathTracer {
+    heuristic: Heuristic,
+    roulette_start: usize,
+}
+
+impl PathTracer {
+    #[inline(always)]
+    pub fn new() -> PathTracer {
+        PathTracer {
+            heuristic: Heuristic::default(),
+            roulette_start: 3,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_heuristic(mut self, heuristic: Heuristic) -> PathTracer {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// The number of bounces every path survives before Russian roulette starts ending them.
+    #[inline(always)]
+    pub fn with_roulette_start(mut self, bounces: usize) -> PathTracer {
+        self.roulette_start = bounces;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_heuristic(&self) -> Heuristic {
+        self.heuristic
+    }
+
+    #[inline(always)]
+    pub fn get_roulette_start(&self) -> usize {
+        self.roulette_start
+    }
+
+    // Light reaching a diffuse surface straight from the light sources. Every delta light is
+    // checked, and the emitters and background get one sample each, weighted against the
+    // material sampling the same direction.
+    fn sample_lights(&self, r: &Ray, rec: &HitRecord, scene: &Scene) -> Vec3 {
+        let world = scene.get_world();
+        let material = rec.get_material();
+        let point = rec.get_point();
+        let black = Vec3::new(0.0, 0.0, 0.0);
+        let mut total = black;
+
+        for light in scene.get_lights() {
+            if let Some(sample) = light.sample_li(point) {
+                let f = material.eval(r, rec, sample.get_direction());
+                let shadow_ray = Ray::new(point, sample.get_direction(), r.get_time());
+                if f != black && !world.occluded(&shadow_ray, 0.001, sample.get_distance() - 0.001)
+                {
+                    total += f * sample.get_radiance();
+                }
+            }
+        }
+
+        // Whatever is hit first counts, the density covers every emitter along the way.
+        let emitters = scene.get_emitters();
+        if !emitters.is_empty() {
+            let direction = emitters.random_direction(point, random());
+            let light_pdf = emitters.pdf_value(point, direction);
+            let f = material.eval(r, rec, direction);
+            if light_pdf > 0.0 && f != black {
+                let light_ray = Ray::new(point, direction, r.get_time());
+                if let Some(light_rec) = world.hit(&light_ray, 0.001, f64::INFINITY) {
+                    let (u, v) = light_rec.get_uv();
+                    let emitted = light_rec
+                        .get_material()
+                        .emitted(u, v, light_rec.get_point());
+                    let weight = self
+                        .heuristic
+                        .weight(light_pdf, material.pdf(r, rec, direction));
+                    total += (weight / light_pdf) * (f * emitted);
+                }
+            }
+        }
+
+        let background = scene.get_background();
+        if let Some((direction, light_pdf)) = background.sample_direction(random()) {
+            let f = material.eval(r, rec, direction);
+            let light_ray = Ray::new(point, direction, r.get_time());
+            if light_pdf > 0.0 && f != black && !world.occluded(&light_ray, 0.001, f64::INFINITY) {
+                let weight = self
+                    .heuristic
+                    .weight(light_pdf, material.pdf(r, rec, direction));
+                total += (weight / light_pdf) * (f * background.radiance(direction));
+            }
+        }
+
+        total
+    }
+}
+
+impl Default for PathTracer {
+    fn default() -> PathTracer {
+        PathTracer::new()
+    }
+}
+
+impl Integrator for PathTracer {
+    fn li(&self, r: &Ray, scene: &Scene) -> Vec3 {
+        self.trace(r, scene, false).0
+    }
+
+    fn li_with_first_hit(&self, r: &Ray, scene: &Scene) -> (Vec3, Option<HitRecord>) {
+        self.trace(r, scene, true)
+    }
+}
+
+impl PathTracer {
+    // The radiance along `r`, and a copy of its first hit when `keep_first_hit` is set.
+    fn trace(&self, r: &Ray, scene: &Scene, keep_first_hit: bool) -> (Vec3, Option<HitRecord>) {
+        let world = scene.get_world();
+        let mut radiance = Vec3::new(0.0, 0.0, 0.0);
+        let mut throughput = Vec3::new(1.0, 1.0, 1.0);
+        let mut ray = *r;
+        // The density the last bounce picked `ray` with. `None` for camera rays and specular
+        // bounces, which light sampling can never reproduce, so they keep all emitted light.
+        let mut bsdf_pdf: Option<f64> = None;
+        let mut bounces = 0;
+        let mut first_hit = None;
+
+        loop {
+            let rec = match world.hit(&ray, 0.001, f64::INFINITY) {
+                Some(rec) => rec,
+                None => {
+                    let background = scene.get_background();
+                    let weight = bsdf_pdf.map_or(1.0, |pdf| {
+                        let light_pdf = background.pdf_direction(ray.get_direction());
+                        self.heuristic.weight(pdf, light_pdf)
+                    });
+                    radiance += weight * (throughput * background.radiance(ray.get_direction()));
+                    break;
+                }
+            };
+            if keep_first_hit && bounces == 0 {
+                first_hit = Some(rec.clone());
+            }
+
+            let material = rec.get_material();
+            let (u, v) = rec.get_uv();
+            let emitted = material.emitted(u, v, rec.get_point());
+            if emitted != Vec3::new(0.0, 0.0, 0.0) {
+                let weight = bsdf_pdf.map_or(1.0, |pdf| {
+                    let light_pdf = scene
+                        .get_emitters()
+                        .pdf_value(ray.get_origin(), ray.get_direction());
+                    self.heuristic.weight(pdf, light_pdf)
+                });
+                radiance += weight * (throughput * emitted);
+            }
+
+            match material.sample(&ray, &rec) {
+                Some(ScatterRecord::Specular {
+                    attenuation,
+                    ray: scattered,
+                }) => {
+                    throughput *= Vec3::from(attenuation);
+                    ray = scattered;
+                    bsdf_pdf = None;
+                }
+                Some(ScatterRecord::Diffuse { pdf }) => {
+                    radiance += throughput * self.sample_lights(&ray, &rec, scene);
+                    let direction = pdf.generate(random());
+                    let pdf_value = pdf.value(direction);
+                    if pdf_value <= 0.0 {
+                        break;
+                    }
+                    throughput *= material.eval(&ray, &rec, direction) / pdf_value;
+                    ray = Ray::new(rec.get_point(), direction, ray.get_time());
+                    bsdf_pdf = Some(pdf_value);
+                }
+                None => break,
+            }
+
+            // Dim paths are likely to end, survivors are brightened to make up for the rest.
+            bounces += 1;
+            if bounces >= self.roulette_start {
+                let survival = throughput
+                    .get_x()
+                    .max(throughput.get_y())
+                    .max(throughput.get_z())
+                    .min(0.95);
+                if survival <= 0.0 || random::<f64>() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+        }
+        (radiance, first_hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    use crate::backgrounds::solid_background::SolidBackground;
+    use crate::color::Color;
+    use crate::lights::point_light::PointLight;
+    use crate::objects::aarect::XZRect;
+    use crate::objects::lambertian::Lambertian;
+    use crate::objects::sphere::Sphere;
+    use crate::vec3::Point3;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_heuristic_weights_sum_to_one() {
+        for heuristic in &[Heuristic::Balance, Heuristic::Power] {
+            let sum = heuristic.weight(0.3, 2.0) + heuristic.weight(2.0, 0.3);
+            assert!((sum - 1.0).abs() < 1e-12);
+            assert_eq!(heuristic.weight(1.0, 0.0), 1.0);
+        }
+        assert!(Heuristic::Power.weight(2.0, 0.3) > Heuristic::Balance.weight(2.0, 0.3));
+        assert_eq!(Heuristic::Power.weight(f64::INFINITY, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_diffuse_sphere_in_uniform_light() {
+        // Light reflected off a convex object escapes straight away, so every path returns
+        // exactly the albedo.
+        let material = Arc::new(Lambertian::new(Color::new(255, 102, 51)));
+        let sphere = Arc::new(Sphere::new_immobile(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            material,
+        ));
+        let scene = Scene::new(
+            sphere,
+            Arc::new(SolidBackground::new(Vec3::new(1.0, 1.0, 1.0))),
+        );
+        let r = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let integrator = PathTracer::new();
+        for _ in 0..16 {
+            assert_near(integrator.li(&r, &scene), Vec3::new(1.0, 0.4, 0.2));
+        }
+    }
+
+    #[test]
+    fn test_point_light_on_floor() {
+        let material = Arc::new(Lambertian::new(Color::new(255, 255, 255)));
+        let floor = Arc::new(XZRect::new(-10.0, 10.0, -10.0, 10.0, 0.0, material));
+        let light = PointLight::new(Point3::new(0.0, 2.0, 0.0), Color::new(255, 255, 255), 8.0);
+        let scene = Scene::new(floor, Arc::new(SolidBackground::black()))
+            .with_lights(vec![Arc::new(light)]);
+        let r = Ray::new(Point3::new(1.0, 1.0, 0.0), Vec3::new(-1.0, -1.0, 0.0), 0.0);
+        let expected = 2.0 / PI;
+        for heuristic in &[Heuristic::Balance, Heuristic::Power] {
+            let integrator = PathTracer::new().with_heuristic(*heuristic);
+            assert_near(
+                integrator.li(&r, &scene),
+                Vec3::new(expected, expected, expected),
+            );
+        }
+    }
+}
@@ -2,9 +2,26 @@ extern crate image;
 extern crate rand;
 extern crate rayon;
 
+pub mod aov;
+pub mod backgrounds;
 pub mod camera;
 pub mod color;
+pub mod denoise;
+pub mod exr;
+pub mod film;
+pub mod integrators;
+pub mod lights;
+pub mod loaders;
+pub mod noise;
 pub mod objects;
+pub mod onb;
+pub mod pdfs;
+pub mod pfm;
 pub mod ppm;
 pub mod ray;
+pub mod sampling;
+pub mod scene;
+pub mod textures;
+pub mod tone_mapping;
+pub mod transform;
 pub mod vec3;
@@ -0,0 +1,54 @@
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+use crate::lights::light::{Light, LightSample};
+
+/// Parallel light from infinitely far away, like the sun. `direction` is the way the light
+/// travels, so a sun overhead points straight down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirectionalLight {
+    direction: Vec3,
+    radiance: Vec3,
+}
+
+impl DirectionalLight {
+    #[inline(always)]
+    pub fn new(direction: Vec3, color: Color, intensity: f64) -> DirectionalLight {
+        DirectionalLight {
+            direction: direction.unit_vector(),
+            radiance: intensity * Vec3::from(color),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_direction(&self) -> Vec3 {
+        self.direction
+    }
+}
+
+impl Light for DirectionalLight {
+    #[inline(always)]
+    fn sample_li(&self, _point: Point3) -> Option<LightSample> {
+        Some(LightSample::new(
+            -self.direction,
+            f64::INFINITY,
+            self.radiance,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_everywhere() {
+        let light = DirectionalLight::new(Vec3::new(0.0, -2.0, 0.0), Color::new(255, 0, 0), 3.0);
+        for p in &[Point3::new(0.0, 0.0, 0.0), Point3::new(100.0, -50.0, 7.0)] {
+            let sample = light.sample_li(*p).unwrap();
+            assert_eq!(sample.get_direction(), Vec3::new(0.0, 1.0, 0.0));
+            assert_eq!(sample.get_distance(), f64::INFINITY);
+            assert_eq!(sample.get_radiance(), Vec3::new(3.0, 0.0, 0.0));
+        }
+    }
+}
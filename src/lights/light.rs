@@ -0,0 +1,45 @@
+use crate::vec3::{Point3, Vec3};
+
+/// Light reaching a point from one light source, see `Light::sample_li`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightSample {
+    direction: Vec3,
+    distance: f64,
+    radiance: Vec3,
+}
+
+impl LightSample {
+    #[inline(always)]
+    pub fn new(direction: Vec3, distance: f64, radiance: Vec3) -> LightSample {
+        LightSample {
+            direction,
+            distance,
+            radiance,
+        }
+    }
+
+    /// Unit direction from the receiving point towards the light.
+    #[inline(always)]
+    pub fn get_direction(&self) -> Vec3 {
+        self.direction
+    }
+
+    /// How far away the light is, infinite for lights without a position.
+    #[inline(always)]
+    pub fn get_distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Radiance arriving at the point, assuming nothing is in the way.
+    #[inline(always)]
+    pub fn get_radiance(&self) -> Vec3 {
+        self.radiance
+    }
+}
+
+/// A light that is not part of the geometry. Rays never hit these lights, so their light only
+/// gets into the image by sampling them directly with shadow rays.
+pub trait Light {
+    /// The light arriving at `point`, or `None` if it gets nothing from this light.
+    fn sample_li(&self, point: Point3) -> Option<LightSample>;
+}
@@ -0,0 +1,4 @@
+pub mod directional_light;
+pub mod light;
+pub mod point_light;
+pub mod spot_light;
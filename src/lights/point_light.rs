@@ -0,0 +1,60 @@
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+use crate::lights::light::{Light, LightSample};
+
+/// An infinitely small light shining equally in every direction, falling off with the square
+/// of the distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    position: Point3,
+    intensity: Vec3,
+}
+
+impl PointLight {
+    #[inline(always)]
+    pub fn new(position: Point3, color: Color, intensity: f64) -> PointLight {
+        PointLight {
+            position,
+            intensity: intensity * Vec3::from(color),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_position(&self) -> Point3 {
+        self.position
+    }
+}
+
+impl Light for PointLight {
+    fn sample_li(&self, point: Point3) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        Some(LightSample::new(
+            to_light / distance,
+            distance,
+            self.intensity / distance_squared,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inverse_square_falloff() {
+        let light = PointLight::new(Point3::new(0.0, 4.0, 0.0), Color::new(255, 255, 255), 16.0);
+        let sample = light.sample_li(Point3::new(0.0, 0.0, 0.0)).unwrap();
+        assert_eq!(sample.get_direction(), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(sample.get_distance(), 4.0);
+        assert_eq!(sample.get_radiance(), Vec3::new(1.0, 1.0, 1.0));
+
+        let far = light.sample_li(Point3::new(0.0, -4.0, 0.0)).unwrap();
+        assert_eq!(far.get_radiance(), Vec3::new(0.25, 0.25, 0.25));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+use crate::lights::light::{Light, LightSample};
+
+/// A point light that only shines inside a cone. Full brightness inside `falloff_start`
+/// degrees of the axis, fading smoothly to nothing at `cone_angle` degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotLight {
+    position: Point3,
+    direction: Vec3,
+    intensity: Vec3,
+    cos_cone: f64,
+    cos_falloff_start: f64,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point3,
+        direction: Vec3,
+        color: Color,
+        intensity: f64,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> SpotLight {
+        SpotLight {
+            position,
+            direction: direction.unit_vector(),
+            intensity: intensity * Vec3::from(color),
+            cos_cone: cone_angle.to_radians().cos(),
+            cos_falloff_start: falloff_start.min(cone_angle).to_radians().cos(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_position(&self) -> Point3 {
+        self.position
+    }
+
+    #[inline(always)]
+    pub fn get_direction(&self) -> Vec3 {
+        self.direction
+    }
+
+    // Smoothstep between the edge of the cone and the start of the falloff.
+    fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta >= self.cos_falloff_start {
+            1.0
+        } else if cos_theta <= self.cos_cone {
+            0.0
+        } else {
+            let t = (cos_theta - self.cos_cone) / (self.cos_falloff_start - self.cos_cone);
+            t * t * (3.0 - (2.0 * t))
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn sample_li(&self, point: Point3) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let falloff = self.falloff(-direction.dot(&self.direction));
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(LightSample::new(
+            direction,
+            distance,
+            (falloff / distance_squared) * self.intensity,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cone_falloff() {
+        let light = SpotLight::new(
+            Point3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Color::new(255, 255, 255),
+            1.0,
+            45.0,
+            30.0,
+        );
+        let radiance_at = |x: f64| {
+            light
+                .sample_li(Point3::new(x, 0.0, 0.0))
+                .map(|s| s.get_radiance().get_x() * (1.0 + (x * x)))
+        };
+        // Straight below and inside the inner cone at full strength.
+        assert_eq!(radiance_at(0.0), Some(1.0));
+        assert!((radiance_at(0.5).unwrap() - 1.0).abs() < 1e-12);
+        // 40 degrees out is partway through the falloff, 50 is outside the cone.
+        let partial = radiance_at(40.0_f64.to_radians().tan()).unwrap();
+        assert!(partial > 0.0 && partial < 1.0);
+        assert_eq!(radiance_at(50.0_f64.to_radians().tan()), None);
+        assert_eq!(light.sample_li(Point3::new(0.0, 2.0, 0.0)), None);
+    }
+}
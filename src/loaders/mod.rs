@@ -0,0 +1,88 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::vec3::Vec3;
+
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, std::io::Error),
+    /// A texture referenced by the file could not be read.
+    Image(PathBuf, image::ImageError),
+    /// A problem in a text format, located by line.
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+    /// A problem in a binary format or in the file as a whole.
+    Format {
+        file: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            LoadError::Image(path, err) => write!(f, "{}: {}", path.display(), err),
+            LoadError::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            LoadError::Format { file, message } => write!(f, "{}: {}", file, message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+// File formats store colors as floats that are not always kept inside [0, 1], or even finite.
+// NaN becomes black.
+fn to_color(v: Vec3) -> Color {
+    let channel = |x: f64| {
+        let x = if x.is_nan() { 0.0 } else { x.clamp(0.0, 1.0) };
+        (x * 255.999) as u8
+    };
+    Color::new(channel(v.get_x()), channel(v.get_y()), channel(v.get_z()))
+}
+
+// A normal read from a file may be zero or not finite, which gives no direction to shade with.
+// Those are left out, so the mesh falls back to its geometric normals.
+fn to_normal(v: Vec3) -> Option<Vec3> {
+    let length = v.length();
+    if length > 0.0 && length.is_finite() {
+        Some(v / length)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nan_color_is_black() {
+        assert_eq!(
+            to_color(Vec3::new(f64::NAN, 2.0, 0.5)),
+            Color::new(0, 255, 127)
+        );
+    }
+
+    #[test]
+    fn test_to_normal() {
+        assert_eq!(
+            to_normal(Vec3::new(0.0, 3.0, 0.0)),
+            Some(Vec3::new(0.0, 1.0, 0.0))
+        );
+        assert_eq!(to_normal(Vec3::new(0.0, 0.0, 0.0)), None);
+        assert_eq!(to_normal(Vec3::new(f64::INFINITY, 0.0, 0.0)), None);
+    }
+}
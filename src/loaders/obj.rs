@@ -0,0 +1,529 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::SplitWhitespace;
+use std::sync::Arc;
+
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::bvh::{Bvh, SplitMethod};
+use crate::objects::dielectric::Dielectric;
+use crate::objects::hittable::Hittable;
+use crate::objects::lambertian::Lambertian;
+use crate::objects::material::Material;
+use crate::objects::metal::Metal;
+use crate::objects::triangle_mesh::TriangleMesh;
+use crate::textures::image_texture::ImageTexture;
+
+use crate::loaders::{to_color, to_normal, LoadError};
+
+/// A material statement from an MTL file, only the parameters the crate's materials can use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MtlMaterial {
+    pub name: String,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub shininess: f64,
+    pub index_of_refraction: f64,
+    pub dissolve: f64,
+    pub illum: Option<u32>,
+    pub diffuse_map: Option<PathBuf>,
+}
+
+impl MtlMaterial {
+    pub fn new(name: &str) -> MtlMaterial {
+        MtlMaterial {
+            name: name.to_string(),
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::new(0.0, 0.0, 0.0),
+            shininess: 0.0,
+            index_of_refraction: 1.5,
+            dissolve: 1.0,
+            illum: None,
+            diffuse_map: None,
+        }
+    }
+
+    /// Picks the closest of the crate's materials: anything transparent becomes a `Dielectric`,
+    /// reflective illumination models or a purely specular color become a `Metal` whose fuzz
+    /// shrinks as the specular exponent grows, everything else is `Lambertian`. A diffuse map
+    /// replaces the diffuse color and is loaded here.
+    pub fn to_material(&self) -> Result<Arc<dyn Material + Sync + Send>, LoadError> {
+        let is_zero = |v: Vec3| v.get_x() <= 0.0 && v.get_y() <= 0.0 && v.get_z() <= 0.0;
+        let transparent = self.dissolve < 1.0 || matches!(self.illum, Some(4) | Some(6) | Some(7));
+        let reflective = matches!(self.illum, Some(3) | Some(5))
+            || (is_zero(self.diffuse) && !is_zero(self.specular));
+        Ok(if transparent {
+            Arc::new(Dielectric::new(self.index_of_refraction))
+        } else if reflective {
+            let fuzz = 1.0 - (self.shininess / 1000.0).clamp(0.0, 1.0).sqrt();
+            Arc::new(Metal::new(to_color(self.specular), fuzz))
+        } else if let Some(path) = &self.diffuse_map {
+            let texture =
+                ImageTexture::open(path).map_err(|e| LoadError::Image(path.clone(), e))?;
+            Arc::new(Lambertian::from_texture(Arc::new(texture)))
+        } else {
+            Arc::new(Lambertian::new(to_color(self.diffuse)))
+        })
+    }
+}
+
+/// A run of faces sharing a group (`g`/`o`) and material (`usemtl`).
+pub struct ObjGroup {
+    pub name: String,
+    pub material_name: Option<String>,
+    pub mesh: TriangleMesh,
+}
+
+pub struct ObjModel {
+    pub groups: Vec<ObjGroup>,
+}
+
+impl ObjModel {
+    pub fn triangle_count(&self) -> usize {
+        self.groups.iter().map(|g| g.mesh.len()).sum()
+    }
+
+    /// A hierarchy over the groups, each of which is a mesh with its own hierarchy.
+    pub fn into_bvh(self) -> Bvh {
+        let meshes = self
+            .groups
+            .into_iter()
+            .map(|g| Arc::new(g.mesh) as Arc<dyn Hittable + Sync + Send>)
+            .collect();
+        Bvh::from_shared(meshes, 0.0, 1.0, SplitMethod::default(), 4)
+    }
+}
+
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<ObjModel, LoadError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_obj(BufReader::new(file), &path.display().to_string(), base_dir)
+}
+
+pub fn load_mtl<P: AsRef<Path>>(path: P) -> Result<HashMap<String, MtlMaterial>, LoadError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_mtl(BufReader::new(file), &path.display().to_string(), base_dir)
+}
+
+// Collects the faces of one group, merging the separate position/uv/normal index streams of the
+// OBJ format into the single index per corner that `TriangleMesh` uses.
+struct GroupBuilder {
+    name: String,
+    material_name: Option<String>,
+    vertex_lookup: HashMap<(usize, Option<usize>, Option<usize>), usize>,
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    indices: Vec<[usize; 3]>,
+    has_normals: bool,
+    has_uvs: bool,
+}
+
+impl GroupBuilder {
+    fn new(name: &str, material_name: Option<String>) -> GroupBuilder {
+        GroupBuilder {
+            name: name.to_string(),
+            material_name,
+            vertex_lookup: HashMap::new(),
+            positions: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            indices: Vec::new(),
+            has_normals: true,
+            has_uvs: true,
+        }
+    }
+
+    fn vertex(&mut self, corner: (usize, Option<usize>, Option<usize>), data: &ObjData) -> usize {
+        if let Some(&index) = self.vertex_lookup.get(&corner) {
+            return index;
+        }
+        let (p, t, n) = corner;
+        let index = self.positions.len();
+        self.positions.push(data.positions[p]);
+        self.uvs.push(t.map_or((0.0, 0.0), |t| data.uvs[t]));
+        let normal = n.and_then(|n| data.normals[n]);
+        self.normals
+            .push(normal.unwrap_or_else(|| Vec3::new(0.0, 0.0, 0.0)));
+        self.has_uvs &= t.is_some();
+        self.has_normals &= normal.is_some();
+        self.vertex_lookup.insert(corner, index);
+        index
+    }
+
+    fn finish(self, material: Arc<dyn Material + Sync + Send>) -> ObjGroup {
+        let normals = if self.has_normals {
+            self.normals
+        } else {
+            Vec::new()
+        };
+        let uvs = if self.has_uvs { self.uvs } else { Vec::new() };
+        ObjGroup {
+            name: self.name,
+            material_name: self.material_name,
+            mesh: TriangleMesh::new(self.positions, normals, uvs, self.indices, material),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ObjData {
+    positions: Vec<Point3>,
+    // `None` for normals without a direction, see `to_normal`.
+    normals: Vec<Option<Vec3>>,
+    uvs: Vec<(f64, f64)>,
+}
+
+struct LineContext<'a> {
+    file: &'a str,
+    line: usize,
+}
+
+impl LineContext<'_> {
+    fn error<T>(&self, message: String) -> Result<T, LoadError> {
+        Err(LoadError::Parse {
+            file: self.file.to_string(),
+            line: self.line,
+            message,
+        })
+    }
+
+    fn float(&self, tokens: &mut SplitWhitespace, what: &str) -> Result<f64, LoadError> {
+        match tokens.next() {
+            Some(tok) => self.parse_float(tok, what),
+            None => self.error(format!("missing component in {}", what)),
+        }
+    }
+
+    fn optional_float(
+        &self,
+        tokens: &mut SplitWhitespace,
+        what: &str,
+        default: f64,
+    ) -> Result<f64, LoadError> {
+        match tokens.next() {
+            Some(tok) => self.parse_float(tok, what),
+            None => Ok(default),
+        }
+    }
+
+    fn parse_float(&self, token: &str, what: &str) -> Result<f64, LoadError> {
+        match token.parse::<f64>() {
+            Ok(v) => Ok(v),
+            Err(_) => self.error(format!("invalid number '{}' in {}", token, what)),
+        }
+    }
+
+    fn vec3(&self, tokens: &mut SplitWhitespace, what: &str) -> Result<Vec3, LoadError> {
+        Ok(Vec3::new(
+            self.float(tokens, what)?,
+            self.float(tokens, what)?,
+            self.float(tokens, what)?,
+        ))
+    }
+
+    // OBJ indices are 1-based, negative ones count back from the most recent element.
+    fn index(&self, token: &str, count: usize, what: &str) -> Result<usize, LoadError> {
+        let i: i64 = match token.parse() {
+            Ok(i) => i,
+            Err(_) => return self.error(format!("invalid {} index '{}'", what, token)),
+        };
+        let resolved = if i > 0 { i - 1 } else { count as i64 + i };
+        if i == 0 || resolved < 0 || resolved >= count as i64 {
+            self.error(format!(
+                "{} index {} out of range ({} defined)",
+                what, i, count
+            ))
+        } else {
+            Ok(resolved as usize)
+        }
+    }
+
+    fn corner(
+        &self,
+        token: &str,
+        data: &ObjData,
+    ) -> Result<(usize, Option<usize>, Option<usize>), LoadError> {
+        let mut parts = token.split('/');
+        let p = self.index(parts.next().unwrap_or(""), data.positions.len(), "vertex")?;
+        let t = match parts.next() {
+            Some("") | None => None,
+            Some(t) => Some(self.index(t, data.uvs.len(), "texture coordinate")?),
+        };
+        let n = match parts.next() {
+            Some("") | None => None,
+            Some(n) => Some(self.index(n, data.normals.len(), "normal")?),
+        };
+        if parts.next().is_some() {
+            return self.error(format!("malformed face vertex '{}'", token));
+        }
+        Ok((p, t, n))
+    }
+}
+
+/// Parses an OBJ file, loading any `mtllib` relative to `base_dir`. `file` is only used to
+/// label errors.
+pub fn parse_obj<R: BufRead>(
+    reader: R,
+    file: &str,
+    base_dir: &Path,
+) -> Result<ObjModel, LoadError> {
+    let mut data = ObjData::default();
+    let mut materials: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut finished: Vec<GroupBuilder> = Vec::new();
+    let mut current = GroupBuilder::new("default", None);
+
+    for (number, line) in reader.lines().enumerate() {
+        let ctx = LineContext {
+            file,
+            line: number + 1,
+        };
+        let line = line.map_err(|e| LoadError::Io(PathBuf::from(file), e))?;
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(k) => k,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => data.positions.push(ctx.vec3(&mut tokens, "vertex")?),
+            "vn" => data
+                .normals
+                .push(to_normal(ctx.vec3(&mut tokens, "normal")?)),
+            "vt" => {
+                let u = ctx.float(&mut tokens, "texture coordinate")?;
+                let v = ctx.optional_float(&mut tokens, "texture coordinate", 0.0)?;
+                data.uvs.push((u, v));
+            }
+            "f" => {
+                let corners = tokens
+                    .map(|tok| ctx.corner(tok, &data))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return ctx.error(format!("face needs 3 vertices, found {}", corners.len()));
+                }
+                let indices: Vec<usize> = corners
+                    .into_iter()
+                    .map(|c| current.vertex(c, &data))
+                    .collect();
+                // Fan triangulation, fine for the convex polygons OBJ exporters produce.
+                for i in 1..(indices.len() - 1) {
+                    current
+                        .indices
+                        .push([indices[0], indices[i], indices[i + 1]]);
+                }
+            }
+            "g" | "o" => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                let material_name = current.material_name.clone();
+                let previous = std::mem::replace(
+                    &mut current,
+                    GroupBuilder::new(
+                        if name.is_empty() { "default" } else { &name },
+                        material_name,
+                    ),
+                );
+                finished.push(previous);
+            }
+            "usemtl" => {
+                let material_name = match tokens.next() {
+                    Some(m) => m.to_string(),
+                    None => return ctx.error("usemtl without a material name".to_string()),
+                };
+                let name = current.name.clone();
+                let previous =
+                    std::mem::replace(&mut current, GroupBuilder::new(&name, Some(material_name)));
+                finished.push(previous);
+            }
+            "mtllib" => {
+                for lib in tokens {
+                    materials.extend(load_mtl(base_dir.join(lib))?);
+                }
+            }
+            // Smoothing groups, free-form geometry and lines don't affect triangle meshes.
+            _ => {}
+        }
+    }
+    finished.push(current);
+
+    // Groups sharing a material share one instance of it, and of its textures.
+    let mut built: HashMap<Option<String>, Arc<dyn Material + Sync + Send>> = HashMap::new();
+    let mut groups = Vec::new();
+    for group in finished.into_iter().filter(|g| !g.indices.is_empty()) {
+        let material = match built.entry(group.material_name.clone()) {
+            Entry::Occupied(e) => e.get().clone(),
+            Entry::Vacant(e) => {
+                let mtl = match e.key().as_ref().and_then(|m| materials.get(m)) {
+                    Some(mtl) => mtl.to_material()?,
+                    None => MtlMaterial::new("default").to_material()?,
+                };
+                e.insert(mtl).clone()
+            }
+        };
+        groups.push(group.finish(material));
+    }
+    Ok(ObjModel { groups })
+}
+
+/// Parses an MTL file. Texture map paths are resolved relative to `base_dir`.
+pub fn parse_mtl<R: BufRead>(
+    reader: R,
+    file: &str,
+    base_dir: &Path,
+) -> Result<HashMap<String, MtlMaterial>, LoadError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<MtlMaterial> = None;
+
+    for (number, line) in reader.lines().enumerate() {
+        let ctx = LineContext {
+            file,
+            line: number + 1,
+        };
+        let line = line.map_err(|e| LoadError::Io(PathBuf::from(file), e))?;
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(k) => k,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            let name = match tokens.next() {
+                Some(n) => n,
+                None => return ctx.error("newmtl without a material name".to_string()),
+            };
+            if let Some(done) = current.replace(MtlMaterial::new(name)) {
+                materials.insert(done.name.clone(), done);
+            }
+            continue;
+        }
+
+        let mtl = match current.as_mut() {
+            Some(m) => m,
+            None => return ctx.error(format!("'{}' before any newmtl", keyword)),
+        };
+        match keyword {
+            "Kd" => mtl.diffuse = ctx.vec3(&mut tokens, "Kd")?,
+            "Ks" => mtl.specular = ctx.vec3(&mut tokens, "Ks")?,
+            "Ns" => mtl.shininess = ctx.float(&mut tokens, "Ns")?,
+            "Ni" => mtl.index_of_refraction = ctx.float(&mut tokens, "Ni")?,
+            "d" => mtl.dissolve = ctx.float(&mut tokens, "d")?,
+            "Tr" => mtl.dissolve = 1.0 - ctx.float(&mut tokens, "Tr")?,
+            "illum" => {
+                let value = ctx.float(&mut tokens, "illum")?;
+                mtl.illum = Some(value as u32);
+            }
+            "map_Kd" => match tokens.last() {
+                // Options like -s or -o come before the file name.
+                Some(name) => mtl.diffuse_map = Some(base_dir.join(name)),
+                None => return ctx.error("map_Kd without a file name".to_string()),
+            },
+            _ => {}
+        }
+    }
+    if let Some(done) = current {
+        materials.insert(done.name.clone(), done);
+    }
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD: &str = "# a unit quad split across two materials
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+g quad
+usemtl red
+f 1/1/1 2/2/1 3/3/1 4/4/1
+usemtl glass
+f -4 -2 -1
+";
+
+    #[test]
+    fn test_parse_obj_groups() {
+        let model = parse_obj(QUAD.as_bytes(), "quad.obj", Path::new("")).unwrap();
+        assert_eq!(model.groups.len(), 2);
+        assert_eq!(model.triangle_count(), 3);
+
+        let red = &model.groups[0];
+        assert_eq!(red.name, "quad");
+        assert_eq!(red.material_name.as_deref(), Some("red"));
+        assert_eq!(red.mesh.get_indices(), &[[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(red.mesh.get_normals().len(), 4);
+        assert_eq!(red.mesh.get_uvs()[2], (1.0, 1.0));
+
+        let glass = &model.groups[1];
+        assert_eq!(glass.material_name.as_deref(), Some("glass"));
+        assert_eq!(glass.mesh.get_positions().len(), 3);
+        assert!(glass.mesh.get_normals().is_empty());
+        assert!(glass.mesh.get_uvs().is_empty());
+        assert_eq!(model.into_bvh().stats().primitive_count, 2);
+    }
+
+    #[test]
+    fn test_parse_mtl() {
+        let mtl = "newmtl red\nKd 1 0 0\nmap_Kd -s 2 2 2 bricks.png\n\
+                   newmtl glass\nNi 1.33\nd 0.1\n";
+        let materials = parse_mtl(mtl.as_bytes(), "test.mtl", Path::new("textures")).unwrap();
+        let red = &materials["red"];
+        assert_eq!(red.diffuse, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(red.diffuse_map, Some(PathBuf::from("textures/bricks.png")));
+        let glass = &materials["glass"];
+        assert_eq!(glass.index_of_refraction, 1.33);
+        assert_eq!(glass.dissolve, 0.1);
+    }
+
+    #[test]
+    fn test_nan_diffuse_loads() {
+        let mtl = "newmtl odd\nKd nan 0.5 0.5\n";
+        let materials = parse_mtl(mtl.as_bytes(), "odd.mtl", Path::new("")).unwrap();
+        assert!(materials["odd"].to_material().is_ok());
+    }
+
+    #[test]
+    fn test_zero_normal_is_left_out() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 0\nvn 0 0 2\nf 1//1 2//2 3//2\n";
+        let model = parse_obj(obj.as_bytes(), "zero.obj", Path::new("")).unwrap();
+        assert!(model.groups[0].mesh.get_normals().is_empty());
+
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 0\nvn 0 0 2\nf 1//2 2//2 3//2\n";
+        let model = parse_obj(obj.as_bytes(), "zero.obj", Path::new("")).unwrap();
+        assert_eq!(
+            model.groups[0].mesh.get_normals()[0],
+            Vec3::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let bad_index = "v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 4\n";
+        let err = parse_obj(bad_index.as_bytes(), "bad.obj", Path::new("")).err();
+        assert_eq!(
+            err.map(|e| e.to_string()),
+            Some("bad.obj:5: vertex index 4 out of range (3 defined)".to_string())
+        );
+
+        let bad_number = "v 0 0 0\nv 1 zero 0\n";
+        let err = parse_obj(bad_number.as_bytes(), "bad.obj", Path::new("")).err();
+        assert_eq!(
+            err.map(|e| e.to_string()),
+            Some("bad.obj:2: invalid number 'zero' in vertex".to_string())
+        );
+    }
+}
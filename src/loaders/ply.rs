@@ -0,0 +1,549 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::material::Material;
+use crate::objects::triangle_mesh::TriangleMesh;
+
+use crate::loaders::{to_color, to_normal, LoadError};
+
+// Element counts come from the header, so space is only reserved up to this many ahead of the
+// data actually arriving.
+const MAX_RESERVE: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        match name {
+            "char" | "int8" => Some(ScalarType::I8),
+            "uchar" | "uint8" => Some(ScalarType::U8),
+            "short" | "int16" => Some(ScalarType::I16),
+            "ushort" | "uint16" => Some(ScalarType::U16),
+            "int" | "int32" => Some(ScalarType::I32),
+            "uint" | "uint32" => Some(ScalarType::U32),
+            "float" | "float32" => Some(ScalarType::F32),
+            "double" | "float64" => Some(ScalarType::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    // Integer color channels run up to the largest value of their type, negative ones are
+    // clamped to black later. Floating point ones are already in [0, 1].
+    fn color_scale(self) -> f64 {
+        match self {
+            ScalarType::I8 => 1.0 / i8::MAX as f64,
+            ScalarType::U8 => 1.0 / u8::MAX as f64,
+            ScalarType::I16 => 1.0 / i16::MAX as f64,
+            ScalarType::U16 => 1.0 / u16::MAX as f64,
+            ScalarType::I32 => 1.0 / i32::MAX as f64,
+            ScalarType::U32 => 1.0 / u32::MAX as f64,
+            ScalarType::F32 | ScalarType::F64 => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Property {
+    Scalar {
+        name: String,
+        ty: ScalarType,
+    },
+    List {
+        name: String,
+        count_ty: ScalarType,
+        item_ty: ScalarType,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+// Reads scalars from the body of the file, whitespace separated tokens for ASCII files and raw
+// bytes for the binary variants.
+struct Body<R> {
+    reader: R,
+    format: Format,
+    file: String,
+    line: usize,
+    tokens: VecDeque<String>,
+}
+
+impl<R: BufRead> Body<R> {
+    fn error<T>(&self, message: String) -> Result<T, LoadError> {
+        match self.format {
+            Format::Ascii => Err(LoadError::Parse {
+                file: self.file.clone(),
+                line: self.line,
+                message,
+            }),
+            _ => Err(LoadError::Format {
+                file: self.file.clone(),
+                message,
+            }),
+        }
+    }
+
+    fn read(&mut self, ty: ScalarType, element: &str) -> Result<f64, LoadError> {
+        match self.format {
+            Format::Ascii => self.read_ascii(element),
+            Format::BinaryLittleEndian => self.read_binary(ty, false, element),
+            Format::BinaryBigEndian => self.read_binary(ty, true, element),
+        }
+    }
+
+    fn read_ascii(&mut self, element: &str) -> Result<f64, LoadError> {
+        while self.tokens.is_empty() {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| LoadError::Io(PathBuf::from(&self.file), e))?;
+            if read == 0 {
+                return self.error(format!("unexpected end of file in element '{}'", element));
+            }
+            self.line += 1;
+            self.tokens
+                .extend(line.split_whitespace().map(|t| t.to_string()));
+        }
+        let token = self.tokens.pop_front().unwrap();
+        match token.parse::<f64>() {
+            Ok(v) => Ok(v),
+            Err(_) => self.error(format!(
+                "invalid number '{}' in element '{}'",
+                token, element
+            )),
+        }
+    }
+
+    fn read_binary(
+        &mut self,
+        ty: ScalarType,
+        big_endian: bool,
+        element: &str,
+    ) -> Result<f64, LoadError> {
+        let mut buf = [0_u8; 8];
+        let bytes = &mut buf[..ty.size()];
+        if self.reader.read_exact(bytes).is_err() {
+            return self.error(format!("unexpected end of file in element '{}'", element));
+        }
+        if big_endian {
+            bytes.reverse();
+        }
+        Ok(match ty {
+            ScalarType::I8 => bytes[0] as i8 as f64,
+            ScalarType::U8 => bytes[0] as f64,
+            ScalarType::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::I32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::U32 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::F32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::F64 => f64::from_le_bytes(buf),
+        })
+    }
+}
+
+pub fn load_ply<P: AsRef<Path>>(
+    path: P,
+    material: Arc<dyn Material + Sync + Send>,
+) -> Result<TriangleMesh, LoadError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    parse_ply(BufReader::new(file), &path.display().to_string(), material)
+}
+
+/// Parses an ASCII or binary PLY file into a mesh. Per-vertex `red`/`green`/`blue` properties
+/// become vertex colors that tint `material`. Polygonal faces are triangulated as fans.
+pub fn parse_ply<R: BufRead>(
+    mut reader: R,
+    file: &str,
+    material: Arc<dyn Material + Sync + Send>,
+) -> Result<TriangleMesh, LoadError> {
+    let (format, elements, header_lines) = parse_header(&mut reader, file)?;
+    let mut body = Body {
+        reader,
+        format,
+        file: file.to_string(),
+        line: header_lines,
+        tokens: VecDeque::new(),
+    };
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut indices = Vec::new();
+
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => {
+                positions.reserve(element.count.min(MAX_RESERVE));
+                for _ in 0..element.count {
+                    let vertex = read_vertex(&mut body, element)?;
+                    positions.push(vertex.position);
+                    if let Some(n) = vertex.normal {
+                        normals.push(n);
+                    }
+                    if let Some(uv) = vertex.uv {
+                        uvs.push(uv);
+                    }
+                    if let Some(c) = vertex.color {
+                        colors.push(c);
+                    }
+                }
+            }
+            "face" => {
+                indices.reserve(element.count.min(MAX_RESERVE));
+                for _ in 0..element.count {
+                    let face = read_face(&mut body, element)?;
+                    for &i in &face {
+                        if i >= positions.len() {
+                            return body.error(format!(
+                                "face refers to vertex {} but only {} are defined",
+                                i,
+                                positions.len()
+                            ));
+                        }
+                    }
+                    for i in 1..face.len().saturating_sub(1) {
+                        indices.push([face[0], face[i], face[i + 1]]);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    skip_element(&mut body, element)?;
+                }
+            }
+        }
+    }
+
+    // A mesh has a normal for every vertex or none at all.
+    if normals.len() != positions.len() {
+        normals.clear();
+    }
+    Ok(TriangleMesh::new(positions, normals, uvs, indices, material).with_colors(colors))
+}
+
+fn parse_header<R: BufRead>(
+    reader: &mut R,
+    file: &str,
+) -> Result<(Format, Vec<Element>, usize), LoadError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut line_number = 0;
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| LoadError::Io(PathBuf::from(file), e))?;
+        line_number += 1;
+        let error = |message: String| LoadError::Parse {
+            file: file.to_string(),
+            line: line_number,
+            message,
+        };
+        if read == 0 {
+            return Err(error("end of file before end_header".to_string()));
+        }
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if line_number == 1 {
+            if tokens != ["ply"] {
+                return Err(error("not a PLY file, missing 'ply' magic".to_string()));
+            }
+            continue;
+        }
+        match tokens.as_slice() {
+            ["format", f, _version] => {
+                format = Some(match *f {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => return Err(error(format!("unknown format '{}'", f))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| error(format!("invalid element count '{}'", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_ty, item_ty, name] => {
+                let property = Property::List {
+                    name: name.to_string(),
+                    count_ty: ScalarType::parse(count_ty)
+                        .ok_or_else(|| error(format!("unknown type '{}'", count_ty)))?,
+                    item_ty: ScalarType::parse(item_ty)
+                        .ok_or_else(|| error(format!("unknown type '{}'", item_ty)))?,
+                };
+                match elements.last_mut() {
+                    Some(e) => e.properties.push(property),
+                    None => return Err(error("property before any element".to_string())),
+                }
+            }
+            ["property", ty, name] => {
+                let property = Property::Scalar {
+                    name: name.to_string(),
+                    ty: ScalarType::parse(ty)
+                        .ok_or_else(|| error(format!("unknown type '{}'", ty)))?,
+                };
+                match elements.last_mut() {
+                    Some(e) => e.properties.push(property),
+                    None => return Err(error("property before any element".to_string())),
+                }
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error(format!("unexpected header line '{}'", line.trim()))),
+        }
+    }
+    match format {
+        Some(format) => Ok((format, elements, line_number)),
+        None => Err(LoadError::Parse {
+            file: file.to_string(),
+            line: line_number,
+            message: "header has no format line".to_string(),
+        }),
+    }
+}
+
+struct Vertex {
+    position: Point3,
+    normal: Option<Vec3>,
+    uv: Option<(f64, f64)>,
+    color: Option<Color>,
+}
+
+fn read_vertex<R: BufRead>(body: &mut Body<R>, element: &Element) -> Result<Vertex, LoadError> {
+    let mut position = [0.0; 3];
+    let mut normal = [None; 3];
+    let mut uv = [None; 2];
+    let mut color = [None; 3];
+    for property in &element.properties {
+        match property {
+            Property::Scalar { name, ty } => {
+                let value = body.read(*ty, &element.name)?;
+                match name.as_str() {
+                    "x" => position[0] = value,
+                    "y" => position[1] = value,
+                    "z" => position[2] = value,
+                    "nx" => normal[0] = Some(value),
+                    "ny" => normal[1] = Some(value),
+                    "nz" => normal[2] = Some(value),
+                    "u" | "s" | "texture_u" | "texture_s" => uv[0] = Some(value),
+                    "v" | "t" | "texture_v" | "texture_t" => uv[1] = Some(value),
+                    "red" | "diffuse_red" => color[0] = Some(value * ty.color_scale()),
+                    "green" | "diffuse_green" => color[1] = Some(value * ty.color_scale()),
+                    "blue" | "diffuse_blue" => color[2] = Some(value * ty.color_scale()),
+                    _ => {}
+                }
+            }
+            Property::List { .. } => skip_property(body, property, &element.name)?,
+        }
+    }
+    Ok(Vertex {
+        position: Point3::new(position[0], position[1], position[2]),
+        normal: match normal {
+            [Some(x), Some(y), Some(z)] => to_normal(Vec3::new(x, y, z)),
+            _ => None,
+        },
+        uv: match uv {
+            [Some(u), Some(v)] => Some((u, v)),
+            _ => None,
+        },
+        color: match color {
+            [Some(r), Some(g), Some(b)] => Some(to_color(Vec3::new(r, g, b))),
+            _ => None,
+        },
+    })
+}
+
+fn read_face<R: BufRead>(body: &mut Body<R>, element: &Element) -> Result<Vec<usize>, LoadError> {
+    let mut face = Vec::new();
+    for property in &element.properties {
+        match property {
+            Property::List {
+                name,
+                count_ty,
+                item_ty,
+            } if name == "vertex_indices" || name == "vertex_index" => {
+                let count = body.read(*count_ty, &element.name)? as usize;
+                for _ in 0..count {
+                    let index = body.read(*item_ty, &element.name)?;
+                    if index < 0.0 {
+                        return body.error(format!("negative vertex index {}", index));
+                    }
+                    face.push(index as usize);
+                }
+            }
+            _ => skip_property(body, property, &element.name)?,
+        }
+    }
+    Ok(face)
+}
+
+fn skip_element<R: BufRead>(body: &mut Body<R>, element: &Element) -> Result<(), LoadError> {
+    for property in &element.properties {
+        skip_property(body, property, &element.name)?;
+    }
+    Ok(())
+}
+
+fn skip_property<R: BufRead>(
+    body: &mut Body<R>,
+    property: &Property,
+    element: &str,
+) -> Result<(), LoadError> {
+    match property {
+        Property::Scalar { ty, .. } => {
+            body.read(*ty, element)?;
+        }
+        Property::List {
+            count_ty, item_ty, ..
+        } => {
+            let count = body.read(*count_ty, element)? as usize;
+            for _ in 0..count {
+                body.read(*item_ty, element)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::objects::lambertian::Lambertian;
+
+    fn material() -> Arc<dyn Material + Sync + Send> {
+        Arc::new(Lambertian::new(Color::new(255, 255, 255)))
+    }
+
+    #[test]
+    fn test_parse_ascii() {
+        let ply = "ply
+format ascii 1.0
+comment a colored quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+        let mesh = parse_ply(ply.as_bytes(), "quad.ply", material()).unwrap();
+        assert_eq!(mesh.get_positions()[2], Point3::new(1.0, 1.0, 0.0));
+        assert_eq!(mesh.get_indices(), &[[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(mesh.get_colors()[1], Color::new(0, 255, 0));
+        assert!(mesh.get_normals().is_empty());
+    }
+
+    #[test]
+    fn test_parse_binary_big_endian() {
+        let mut ply = b"ply\nformat binary_big_endian 1.0\n\
+                        element vertex 3\nproperty double x\nproperty double y\nproperty double z\n\
+                        element face 1\nproperty list uchar uint vertex_indices\n\
+                        property uchar flags\nend_header\n"
+            .to_vec();
+        for v in &[[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.5_f64]] {
+            for c in v {
+                ply.extend_from_slice(&c.to_be_bytes());
+            }
+        }
+        ply.push(3);
+        for i in 0..3_u32 {
+            ply.extend_from_slice(&i.to_be_bytes());
+        }
+        ply.push(7);
+
+        let mesh = parse_ply(&ply[..], "tri.ply", material()).unwrap();
+        assert_eq!(mesh.get_positions()[2], Point3::new(0.0, 2.0, 0.5));
+        assert_eq!(mesh.get_indices(), &[[0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_truncated_ascii_reports_line() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\n\
+                   property float y\nproperty float z\nend_header\n0 0 0\n1 oops 0\n";
+        let err = parse_ply(ply.as_bytes(), "bad.ply", material()).err();
+        assert_eq!(
+            err.map(|e| e.to_string()),
+            Some("bad.ply:9: invalid number 'oops' in element 'vertex'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_huge_count_in_header() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 1000000000000\nproperty float x\n\
+                   property float y\nproperty float z\nend_header\n0 0 0\n";
+        assert!(parse_ply(ply.as_bytes(), "huge.ply", material()).is_err());
+    }
+
+    #[test]
+    fn test_signed_color_channels() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+                   property float z\nproperty short red\nproperty short green\n\
+                   property short blue\nelement face 1\nproperty list uchar int vertex_indices\n\
+                   end_header\n0 0 0 32767 0 -100\n1 0 0 0 32767 0\n0 1 0 0 0 32767\n3 0 1 2\n";
+        let mesh = parse_ply(ply.as_bytes(), "short.ply", material()).unwrap();
+        assert_eq!(mesh.get_colors()[0], Color::new(255, 0, 0));
+        assert_eq!(mesh.get_colors()[1], Color::new(0, 255, 0));
+    }
+
+    #[test]
+    fn test_zero_normal_is_left_out() {
+        let ply = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+                   property float z\nproperty float nx\nproperty float ny\nproperty float nz\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0 0 0 0\n1 0 0 0 0 1\n0 1 0 0 0 1\n3 0 1 2\n";
+        let mesh = parse_ply(ply.as_bytes(), "zero.ply", material()).unwrap();
+        assert!(mesh.get_normals().is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs;
+use std::path::Path;
+use std::str;
+use std::sync::Arc;
+
+use crate::vec3::Point3;
+
+use crate::objects::material::Material;
+use crate::objects::triangle_mesh::TriangleMesh;
+
+use crate::loaders::LoadError;
+
+const HEADER_SIZE: usize = 80;
+const FACET_SIZE: usize = 50;
+
+pub fn load_stl<P: AsRef<Path>>(
+    path: P,
+    material: Arc<dyn Material + Sync + Send>,
+) -> Result<TriangleMesh, LoadError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    parse_stl(&bytes, &path.display().to_string(), material)
+}
+
+/// Parses an ASCII or binary STL file. STL stores every facet with its own copy of the corners,
+/// identical positions are merged so the mesh is indexed like the other formats.
+pub fn parse_stl(
+    bytes: &[u8],
+    file: &str,
+    material: Arc<dyn Material + Sync + Send>,
+) -> Result<TriangleMesh, LoadError> {
+    // Binary files may also start with "solid", so trust a size that matches the facet count.
+    let binary_size = bytes.get(HEADER_SIZE..HEADER_SIZE + 4).map(|c| {
+        HEADER_SIZE + 4 + (FACET_SIZE * u32::from_le_bytes(c.try_into().unwrap()) as usize)
+    });
+    let corners = if binary_size == Some(bytes.len()) {
+        parse_binary(bytes)
+    } else if bytes.starts_with(b"solid") {
+        parse_ascii(bytes, file)?
+    } else {
+        return Err(LoadError::Format {
+            file: file.to_string(),
+            message: match binary_size {
+                Some(expected) => format!(
+                    "binary STL should be {} bytes but is {}",
+                    expected,
+                    bytes.len()
+                ),
+                None => "file is too short to be an STL".to_string(),
+            },
+        });
+    };
+
+    let mut lookup: HashMap<[u64; 3], usize> = HashMap::new();
+    let mut positions = Vec::new();
+    let mut indices = Vec::with_capacity(corners.len() / 3);
+    let mut index_of = |p: Point3| {
+        let key = [
+            p.get_x().to_bits(),
+            p.get_y().to_bits(),
+            p.get_z().to_bits(),
+        ];
+        *lookup.entry(key).or_insert_with(|| {
+            positions.push(p);
+            positions.len() - 1
+        })
+    };
+    for facet in corners.chunks(3) {
+        indices.push([index_of(facet[0]), index_of(facet[1]), index_of(facet[2])]);
+    }
+
+    Ok(TriangleMesh::new(
+        positions,
+        Vec::new(),
+        Vec::new(),
+        indices,
+        material,
+    ))
+}
+
+// The stored facet normals are ignored, they are frequently missing or wrong and the winding
+// order gives the same outward direction.
+fn parse_binary(bytes: &[u8]) -> Vec<Point3> {
+    let read_f32 = |at: usize| f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as f64;
+    bytes[HEADER_SIZE + 4..]
+        .chunks_exact(FACET_SIZE)
+        .enumerate()
+        .flat_map(|(i, _)| {
+            let facet = HEADER_SIZE + 4 + (i * FACET_SIZE);
+            (1..4).map(move |corner| {
+                let at = facet + (corner * 12);
+                Point3::new(read_f32(at), read_f32(at + 4), read_f32(at + 8))
+            })
+        })
+        .collect()
+}
+
+fn parse_ascii(bytes: &[u8], file: &str) -> Result<Vec<Point3>, LoadError> {
+    let text = str::from_utf8(bytes).map_err(|_| LoadError::Format {
+        file: file.to_string(),
+        message: "ASCII STL is not valid UTF-8".to_string(),
+    })?;
+
+    let mut corners = Vec::new();
+    let mut facet_corners = 0;
+    for (number, line) in text.lines().enumerate() {
+        let error = |message: String| LoadError::Parse {
+            file: file.to_string(),
+            line: number + 1,
+            message,
+        };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("vertex") => {
+                let mut coord = || -> Result<f64, LoadError> {
+                    let tok = tokens
+                        .next()
+                        .ok_or_else(|| error("vertex needs 3 coordinates".to_string()))?;
+                    tok.parse()
+                        .map_err(|_| error(format!("invalid number '{}' in vertex", tok)))
+                };
+                corners.push(Point3::new(coord()?, coord()?, coord()?));
+                facet_corners += 1;
+            }
+            Some("endfacet") => {
+                if facet_corners != 3 {
+                    return Err(error(format!(
+                        "facet has {} vertices, only triangles are supported",
+                        facet_corners
+                    )));
+                }
+                facet_corners = 0;
+            }
+            _ => {}
+        }
+    }
+    if facet_corners != 0 || corners.len() % 3 != 0 {
+        return Err(LoadError::Parse {
+            file: file.to_string(),
+            line: text.lines().count(),
+            message: "file ends inside a facet".to_string(),
+        });
+    }
+    Ok(corners)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+
+    fn material() -> Arc<dyn Material + Sync + Send> {
+        Arc::new(Lambertian::new(Color::new(127, 127, 127)))
+    }
+
+    #[test]
+    fn test_parse_ascii_merges_vertices() {
+        let stl = "solid quad
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid quad
+";
+        let mesh = parse_stl(stl.as_bytes(), "quad.stl", material()).unwrap();
+        assert_eq!(mesh.get_positions().len(), 4);
+        assert_eq!(mesh.get_indices(), &[[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn test_parse_ascii_truncated() {
+        let stl = "solid t\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\n";
+        match parse_stl(stl.as_bytes(), "t.stl", material()) {
+            Err(LoadError::Parse { line, .. }) => assert_eq!(line, 5),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_binary_starting_with_solid() {
+        let mut stl = b"solid but actually binary".to_vec();
+        stl.resize(HEADER_SIZE, 0);
+        stl.extend_from_slice(&1_u32.to_le_bytes());
+        let facet: [f32; 12] = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 3.0, 0.0];
+        for f in &facet {
+            stl.extend_from_slice(&f.to_le_bytes());
+        }
+        stl.extend_from_slice(&0_u16.to_le_bytes());
+
+        let mesh = parse_stl(&stl, "tri.stl", material()).unwrap();
+        assert_eq!(
+            mesh.get_positions(),
+            &[
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(3.0, 0.0, 0.0),
+                Point3::new(0.0, 3.0, 0.0)
+            ]
+        );
+        assert_eq!(mesh.get_indices(), &[[0, 1, 2]]);
+    }
+}
@@ -1,29 +1,53 @@
-use std::convert::{From, Into, TryFrom, TryInto};
+use std::convert::TryFrom;
+use std::env;
+use std::path::Path;
+use std::process;
 use std::sync::Arc;
 
-use image::{save_buffer_with_format, ColorType, ImageFormat};
 use rand::prelude::*;
 use rayon::prelude::*;
 
+use raytracer::aov::{Aov, AovBuffer, AovSample};
+use raytracer::backgrounds::background::Background;
+use raytracer::backgrounds::environment_map::EnvironmentMap;
+use raytracer::backgrounds::gradient_background::GradientBackground;
+use raytracer::backgrounds::preetham_sky::PreethamSky;
+use raytracer::backgrounds::solid_background::SolidBackground;
 use raytracer::camera::Camera;
 use raytracer::color::Color;
-use raytracer::ray::Ray;
+use raytracer::denoise::Denoiser;
+use raytracer::film::Film;
+use raytracer::integrators::integrator::Integrator;
+use raytracer::integrators::path_tracer::PathTracer;
+use raytracer::lights::directional_light::DirectionalLight;
+use raytracer::lights::light::Light;
+use raytracer::lights::point_light::PointLight;
+use raytracer::lights::spot_light::SpotLight;
 use raytracer::vec3::{Point3, Vec3};
 
+use raytracer::objects::aarect::{XYRect, XZRect, YZRect};
+use raytracer::objects::bvh::Bvh;
+use raytracer::objects::cuboid::Cuboid;
 use raytracer::objects::dielectric::Dielectric;
+use raytracer::objects::diffuse_light::DiffuseLight;
 use raytracer::objects::hittable::Hittable;
+use raytracer::objects::hittable_list::HittableList;
+use raytracer::objects::instance::Instance;
 use raytracer::objects::lambertian::Lambertian;
-use raytracer::objects::material::Material;
 use raytracer::objects::metal::Metal;
 use raytracer::objects::sphere::Sphere;
+use raytracer::scene::Scene;
+use raytracer::tone_mapping::{ToneMapOperator, ToneMapper};
+use raytracer::transform::Transform;
 
-fn random_scene<R: rand::Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Sync + Send>> {
+fn random_scene<R: rand::Rng + ?Sized>(rng: &mut R) -> HittableList {
     let ground_material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
-    let mut world: Vec<Box<dyn Hittable + Sync + Send>> = vec![Box::new(Sphere::new_immobile(
+    let mut world = HittableList::new();
+    world.add(Arc::new(Sphere::new_immobile(
         Point3::new(0.0, -1000.0, 0.0),
         1000.0,
         ground_material,
-    ))];
+    )));
 
     for a in -11..11 {
         for b in -11..11 {
@@ -40,7 +64,7 @@ fn random_scene<R: rand::Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Sy
                         Vec3::random_with_gen(rng, 0.0, 1.0) * Vec3::random_with_gen(rng, 0.0, 1.0),
                     )
                     .unwrap();
-                    world.push(Box::new(Sphere::new(
+                    world.add(Arc::new(Sphere::new(
                         center,
                         center + Point3::new(0.0, rng.gen_range(0.0..0.5), 0.0),
                         0.0,
@@ -52,14 +76,14 @@ fn random_scene<R: rand::Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Sy
                     // metal
                     let albedo = Color::try_from(Vec3::random_with_gen(rng, 0.5, 1.0)).unwrap();
                     let fuzz = rng.gen_range(0.0..0.5);
-                    world.push(Box::new(Sphere::new_immobile(
+                    world.add(Arc::new(Sphere::new_immobile(
                         center,
                         0.2,
                         Arc::new(Metal::new(albedo, fuzz)),
                     )));
                 } else {
                     // glass
-                    world.push(Box::new(Sphere::new_immobile(
+                    world.add(Arc::new(Sphere::new_immobile(
                         center,
                         0.2,
                         Arc::new(Dielectric::new(1.5)),
@@ -70,21 +94,21 @@ fn random_scene<R: rand::Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Sy
     }
 
     let material1 = Arc::new(Dielectric::new(1.5));
-    world.push(Box::new(Sphere::new_immobile(
+    world.add(Arc::new(Sphere::new_immobile(
         Point3::new(0.0, 1.0, 0.0),
         1.0,
         material1,
     )));
 
     let material2 = Arc::new(Lambertian::new(Color::new(102, 51, 25)));
-    world.push(Box::new(Sphere::new_immobile(
+    world.add(Arc::new(Sphere::new_immobile(
         Point3::new(-4.0, 1.0, 0.0),
         1.0,
         material2,
     )));
 
     let material3 = Arc::new(Metal::new(Color::new(178, 153, 127), 0.0));
-    world.push(Box::new(Sphere::new_immobile(
+    world.add(Arc::new(Sphere::new_immobile(
         Point3::new(4.0, 1.0, 0.0),
         1.0,
         material3,
@@ -93,84 +117,338 @@ fn random_scene<R: rand::Rng + ?Sized>(rng: &mut R) -> Vec<Box<dyn Hittable + Sy
     world
 }
 
-fn ray_color_vec(r: &Ray, world: &dyn Hittable, depth: i32) -> Vec3 {
-    if depth <= 0 {
-        Vec3::new(0.0, 0.0, 0.0)
-    } else if let Some(rec) = world.hit(r, 0.001, f64::INFINITY) {
-        if let Some((attenuation, scattered)) = rec.get_material().scatter(r, &rec) {
-            Vec3::from(attenuation) * ray_color_vec(&scattered, world, depth - 1)
-        } else {
-            Vec3::new(0.0, 0.0, 0.0)
+// The box and, separately, its light.
+fn cornell_box() -> (HittableList, HittableList) {
+    let red = Arc::new(Lambertian::new(Color::new(166, 13, 13)));
+    let white = Arc::new(Lambertian::new(Color::new(186, 186, 186)));
+    let green = Arc::new(Lambertian::new(Color::new(31, 115, 38)));
+    let light = Arc::new(DiffuseLight::new(Color::new(255, 255, 255), 15.0));
+
+    let lamp: Arc<dyn Hittable + Sync + Send> =
+        Arc::new(XZRect::new(213.0, 343.0, 227.0, 332.0, 554.0, light));
+    let mut emitters = HittableList::new();
+    emitters.add(lamp.clone());
+
+    let mut world = HittableList::new();
+    world.add(Arc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 555.0, green)));
+    world.add(Arc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 0.0, red)));
+    world.add(lamp);
+    world.add(Arc::new(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.add(Arc::new(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.add(Arc::new(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    let tall = Arc::new(Cuboid::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    ));
+    world.add(Arc::new(Instance::new(
+        tall,
+        Transform::rotate_y(15.0).then(&Transform::translate(Vec3::new(265.0, 0.0, 295.0))),
+    )));
+    let short = Arc::new(Cuboid::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        white,
+    ));
+    world.add(Arc::new(Instance::new(
+        short,
+        Transform::rotate_y(-18.0).then(&Transform::translate(Vec3::new(130.0, 0.0, 65.0))),
+    )));
+
+    (world, emitters)
+}
+
+// A few spheres on a floor, lit only by point, spot and directional lights.
+fn light_test_scene() -> HittableList {
+    let mut world = HittableList::new();
+    world.add(Arc::new(Sphere::new_immobile(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Color::new(178, 178, 178))),
+    )));
+    world.add(Arc::new(Sphere::new_immobile(
+        Point3::new(-1.5, 0.7, 0.0),
+        0.7,
+        Arc::new(Lambertian::new(Color::new(204, 51, 51))),
+    )));
+    world.add(Arc::new(Sphere::new_immobile(
+        Point3::new(0.0, 0.7, -0.5),
+        0.7,
+        Arc::new(Metal::new(Color::new(204, 204, 204), 0.1)),
+    )));
+    world.add(Arc::new(Sphere::new_immobile(
+        Point3::new(1.5, 0.7, 0.0),
+        0.7,
+        Arc::new(Lambertian::new(Color::new(51, 153, 76))),
+    )));
+    world
+}
+
+// Everything that differs between the scenes `main` can render.
+struct SceneSetup {
+    world: HittableList,
+    camera: Camera,
+    aspect_ratio: f64,
+    background: Arc<dyn Background + Sync + Send>,
+    lights: Vec<Arc<dyn Light + Sync + Send>>,
+    // Emissive objects, also in `world`, that bounces are aimed at.
+    emitters: HittableList,
+}
+
+fn select_scene<R: rand::Rng + ?Sized>(name: Option<&str>, rng: &mut R) -> SceneSetup {
+    match name {
+        Some("cornell") => {
+            let look_from = Point3::new(278.0, 278.0, -800.0);
+            let look_at = Point3::new(278.0, 278.0, 0.0);
+            let (world, emitters) = cornell_box();
+            SceneSetup {
+                world,
+                camera: Camera::new(
+                    look_from,
+                    look_at,
+                    Vec3::new(0.0, 1.0, 0.0),
+                    40.0,
+                    1.0,
+                    0.0,
+                    (look_at - look_from).length(),
+                    0.0,
+                    1.0,
+                ),
+                aspect_ratio: 1.0,
+                background: Arc::new(SolidBackground::black()),
+                lights: Vec::new(),
+                emitters,
+            }
+        }
+        Some("lights") => {
+            let aspect_ratio = 16.0 / 9.0;
+            SceneSetup {
+                world: light_test_scene(),
+                camera: Camera::new(
+                    Point3::new(0.0, 3.0, 9.0),
+                    Point3::new(0.0, 0.5, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                    30.0,
+                    aspect_ratio,
+                    0.0,
+                    9.0,
+                    0.0,
+                    1.0,
+                ),
+                aspect_ratio,
+                background: Arc::new(SolidBackground::from_color(Color::new(51, 76, 127), 0.05)),
+                emitters: HittableList::new(),
+                lights: vec![
+                    Arc::new(PointLight::new(
+                        Point3::new(-3.0, 3.0, 2.0),
+                        Color::new(255, 204, 153),
+                        20.0,
+                    )),
+                    Arc::new(SpotLight::new(
+                        Point3::new(2.5, 5.0, 1.0),
+                        Vec3::new(-0.5, -1.0, -0.2),
+                        Color::new(153, 204, 255),
+                        60.0,
+                        25.0,
+                        15.0,
+                    )),
+                    Arc::new(DirectionalLight::new(
+                        Vec3::new(1.0, -1.0, -1.0),
+                        Color::new(255, 255, 255),
+                        0.5,
+                    )),
+                ],
+            }
+        }
+        _ => {
+            let aspect_ratio = 16.0 / 9.0;
+            SceneSetup {
+                world: random_scene(rng),
+                camera: Camera::new(
+                    Point3::new(13.0, 2.0, 3.0),
+                    Point3::new(0.0, 0.0, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                    20.0,
+                    aspect_ratio,
+                    0.1,
+                    10.0,
+                    0.0,
+                    1.0,
+                ),
+                aspect_ratio,
+                background: Arc::new(GradientBackground::default()),
+                lights: Vec::new(),
+                emitters: HittableList::new(),
+            }
         }
-    } else {
-        let unit_dir = r.get_direction().unit_vector();
-        let t = 0.5 * (unit_dir.get_y() + 1.0);
-        ((1.0 - t) * Vec3::from(Color::new(255, 255, 255)))
-            + (t * Vec3::from(Color::new(128, 179, 255)))
     }
 }
 
-fn main() {
-    // Image
-    let aspect_ratio = 16.0 / 9.0;
-    let image_width = 400;
-    let image_height = (image_width as f64 / aspect_ratio) as usize;
-    let samples_per_pixel = 100;
-    let max_depth = 50;
+// Removes `flag` from the arguments, reporting whether it was there.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    }
+}
 
+// Removes `option` and the value after it from the arguments, returning the value.
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<String> {
+    match args.iter().position(|a| a == option) {
+        Some(i) if i + 1 < args.len() => args.drain(i..(i + 2)).nth(1),
+        _ => None,
+    }
+}
+
+fn main() {
     // RNG
     let mut rng = rand::thread_rng();
 
-    // World
-    let world = random_scene(&mut rng);
-
-    // Camera
-    let look_from = Point3::new(13.0, 2.0, 3.0);
-    let look_at = Point3::new(0.0, 0.0, 0.0);
-    let cam = Camera::new(
-        look_from,
-        look_at,
-        Vec3::new(0.0, 1.0, 0.0),
-        20.0,
-        aspect_ratio,
-        0.1,
-        10.0,
-        0.0,
-        1.0,
-    );
+    // `-o` and a path anywhere in the arguments names the output, its extension picks the
+    // format. `.exr`, `.hdr` and `.pfm` keep the raw radiance.
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let output = take_option(&mut args, "-o").unwrap_or_else(|| String::from("test.png"));
+    // `--tonemap` picks the curve for 8-bit outputs, `aces` unless given: `clamp`,
+    // `reinhard`, `extended-reinhard[:white]`, `hable`, `aces` or `agx`. `--exposure` brightens
+    // by that many stops first.
+    let operator = match take_option(&mut args, "--tonemap") {
+        Some(name) => name.parse().unwrap_or_else(|e| {
+            eprintln!("--tonemap: {}", e);
+            process::exit(2);
+        }),
+        None => ToneMapOperator::AcesFilmic,
+    };
+    let exposure = match take_option(&mut args, "--exposure") {
+        Some(stops) => stops.parse().unwrap_or_else(|_| {
+            eprintln!("--exposure: invalid number of stops '{}'", stops);
+            process::exit(2);
+        }),
+        None => 0.0,
+    };
+    // `--aovs` also writes albedo, normal, depth, position and IDs. They become layers of an
+    // OpenEXR output, or files beside any other, like `test.albedo.png`.
+    let write_aovs = take_flag(&mut args, "--aovs");
+    // `--denoise` filters the image, guided by the AOVs, before it is saved.
+    let denoise = take_flag(&mut args, "--denoise");
+    // `--bvh-stats` prints the shape of the scene's hierarchy.
+    let bvh_stats = take_flag(&mut args, "--bvh-stats");
+
+    // World and camera, `cornell` or `lights` as the first argument picks another scene. `sky`
+    // or a Radiance `.hdr` file as the second replaces the background.
+    let mut setup = select_scene(args.first().map(String::as_str), &mut rng);
+    match args.get(1).map(String::as_str) {
+        Some("sky") => {
+            setup.background = Arc::new(PreethamSky::from_sun_angles(
+                35.0,
+                30.0,
+                3.0,
+                Color::new(76, 76, 76),
+            ))
+        }
+        Some(path) if path.to_ascii_lowercase().ends_with(".hdr") => {
+            match EnvironmentMap::open(path) {
+                Ok(map) => setup.background = Arc::new(map),
+                Err(e) => {
+                    eprintln!("{}: {}", path, e);
+                    process::exit(2);
+                }
+            }
+        }
+        Some(other) => {
+            eprintln!(
+                "unknown background '{}', expected `sky` or a Radiance `.hdr` file",
+                other
+            );
+            process::exit(2);
+        }
+        None => {}
+    }
+    let world = Bvh::from_list(&setup.world, 0.0, 1.0);
+    if bvh_stats {
+        eprintln!("{:?}", world.stats());
+    }
+    let cam = setup.camera;
+    let scene = Scene::new(Arc::new(world), setup.background)
+        .with_lights(setup.lights)
+        .with_emitters(setup.emitters);
+    let integrator = PathTracer::new();
+
+    // Image
+    let image_width = 400;
+    let image_height = (image_width as f64 / setup.aspect_ratio) as usize;
+    let samples_per_pixel = 100;
 
     // Generate
-    let raw_img_buf: Vec<u8> = vec![vec![[0_u8; 3]; image_width]; image_height]
-        .into_par_iter()
+    let mut film = Film::new(image_width, image_height);
+    let mut aovs = AovBuffer::new(image_width, image_height);
+    let forward = cam.get_forward();
+    film.get_pixels_mut()
+        .par_chunks_mut(image_width)
+        .zip(aovs.get_pixels_mut().par_chunks_mut(image_width))
         .enumerate()
-        .flat_map(|(y, row)| {
-            row.into_par_iter()
-                .enumerate()
-                .flat_map(|(x, _s)| {
-                    let mut pixel_color_vec = Vec3::new(0.0, 0.0, 0.0);
-                    for _ in 0..samples_per_pixel {
-                        let u = (x as f64 + random::<f64>()) / (image_width - 1) as f64;
-                        let v = ((image_height - 1 - y) as f64 + random::<f64>())
-                            / (image_height - 1) as f64;
-                        let r = cam.get_ray(u, v);
-                        pixel_color_vec += ray_color_vec(&r, &world, max_depth);
+        .for_each(|(y, (row, aov_row))| {
+            for (x, (pixel, aov_pixel)) in row.iter_mut().zip(aov_row).enumerate() {
+                for _ in 0..samples_per_pixel {
+                    let u = (x as f64 + random::<f64>()) / (image_width - 1) as f64;
+                    let v = ((image_height - 1 - y) as f64 + random::<f64>())
+                        / (image_height - 1) as f64;
+                    let r = cam.get_ray(u, v);
+                    if write_aovs || denoise {
+                        let (radiance, first_hit) = integrator.li_with_first_hit(&r, &scene);
+                        pixel.add_sample(radiance);
+                        let sample = first_hit.map(|rec| AovSample::from_hit(&r, &rec, forward));
+                        aov_pixel.add_sample(sample.as_ref());
+                    } else {
+                        pixel.add_sample(integrator.li(&r, &scene));
                     }
-                    pixel_color_vec.scale_in_range(1.0 / samples_per_pixel as f64, 0.0, 0.999);
-                    let rgb_slice: [u8; 3] = Color::try_from(pixel_color_vec).unwrap().into();
-                    rgb_slice
-                })
-                .collect::<Vec<u8>>()
-        })
-        .collect::<Vec<u8>>();
-
-    // Render
-    save_buffer_with_format(
-        "test.png",
-        &raw_img_buf,
-        image_width.try_into().unwrap(),
-        image_height.try_into().unwrap(),
-        ColorType::Rgb8,
-        ImageFormat::Png,
-    )
-    .unwrap();
+                }
+            }
+        });
+    // Post-process and save
+    if denoise {
+        film = Denoiser::new().denoise(&film, &aovs);
+    }
+    let tone_mapper = ToneMapper::new(operator).with_exposure(exposure);
+    let output = Path::new(&output);
+    let extension = output.extension().and_then(|e| e.to_str()).unwrap_or("png");
+    if write_aovs && extension.eq_ignore_ascii_case("exr") {
+        film.save_exr(output, &aovs.to_layers()).unwrap();
+    } else {
+        film.save(output, &tone_mapper).unwrap();
+    }
+    if write_aovs && !extension.eq_ignore_ascii_case("exr") {
+        let stem = output
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("test");
+        for aov in &Aov::ALL {
+            let name = format!("{}.{}.{}", stem, aov.get_name(), extension);
+            aovs.save(output.with_file_name(name), *aov).unwrap();
+        }
+    }
 }
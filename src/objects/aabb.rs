@@ -2,10 +2,11 @@ use std::cmp::Ordering;
 use std::mem::swap;
 
 use crate::ray::Ray;
-use crate::vec3::Point3;
+use crate::vec3::{Point3, Vec3};
 
 use crate::objects::bounding_box::BoundingBox;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AABB {
     low: Point3,
     high: Point3,
@@ -17,6 +18,11 @@ impl AABB {
         AABB { low, high }
     }
 
+    #[inline(always)]
+    pub fn from_point(p: Point3) -> AABB {
+        AABB { low: p, high: p }
+    }
+
     #[inline(always)]
     pub fn get_low(&self) -> Point3 {
         self.low
@@ -44,27 +50,65 @@ impl AABB {
             high: big,
         }
     }
-}
 
-impl BoundingBox for AABB {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+    #[inline(always)]
+    pub fn get_centroid(&self) -> Point3 {
+        0.5 * (self.low + self.high)
+    }
+
+    #[inline(always)]
+    pub fn get_extent(&self) -> Vec3 {
+        self.high - self.low
+    }
+
+    #[inline(always)]
+    pub fn surface_area(&self) -> f64 {
+        let d = self.get_extent();
+        2.0 * ((d.get_x() * d.get_y()) + (d.get_y() * d.get_z()) + (d.get_z() * d.get_x()))
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let d = self.get_extent();
+        if d.get_x() > d.get_y() && d.get_x() > d.get_z() {
+            0
+        } else if d.get_y() > d.get_z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    // Slab test against a ray whose reciprocal direction has already been computed, so that
+    // traversals testing many boxes with the same ray only divide once.
+    #[inline(always)]
+    pub fn hit_inverse(&self, origin: Point3, inv_dir: Vec3, t_min: f64, t_max: f64) -> bool {
         let mut t_min = t_min;
         let mut t_max = t_max;
         for i in 0..3 {
-            let inv_dir = 1.0 / ray.get_direction()[i];
-            let mut t0 = (self.low[i] - ray.get_origin()[i]) * inv_dir;
-            let mut t1 = (self.high[i] - ray.get_origin()[i]) * inv_dir;
-            if inv_dir < 0.0 {
+            let mut t0 = (self.low[i] - origin[i]) * inv_dir[i];
+            let mut t1 = (self.high[i] - origin[i]) * inv_dir[i];
+            if inv_dir[i] < 0.0 {
                 swap(&mut t0, &mut t1);
             }
             t_min = t0.max(t_min);
             t_max = t1.min(t_max);
-            if t_max <= t_min {
+            // Boxes around flat primitives have zero thickness, so a ray crossing them enters
+            // and leaves at the same t.
+            if t_max < t_min {
                 return false;
             }
         }
         true
     }
+}
+
+impl BoundingBox for AABB {
+    #[inline(always)]
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        let d = ray.get_direction();
+        let inv_dir = Vec3::new(1.0 / d.get_x(), 1.0 / d.get_y(), 1.0 / d.get_z());
+        self.hit_inverse(ray.get_origin(), inv_dir, t_min, t_max)
+    }
 
     #[inline(always)]
     fn get_lower(&self) -> Point3 {
@@ -77,7 +121,9 @@ impl BoundingBox for AABB {
     }
 
     #[inline(always)]
-    fn cmp_axis(&self, other: &Box<dyn BoundingBox>, axis: usize) -> Ordering {
-        self.get_lower()[axis].partial_cmp(&other.get_lower()[axis]).unwrap()
+    fn cmp_axis(&self, other: &dyn BoundingBox, axis: usize) -> Ordering {
+        self.get_lower()[axis]
+            .partial_cmp(&other.get_lower()[axis])
+            .unwrap()
     }
 }
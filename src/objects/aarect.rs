@@ -0,0 +1,360 @@
+use std::sync::Arc;
+
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::aabb::AABB;
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::hittable::{HitRecord, Hittable};
+use crate::objects::material::Material;
+
+// Axis-aligned rectangles have no thickness, so their bounding boxes are padded along the
+// normal axis to keep the slab test from rejecting every ray.
+const PADDING: f64 = 0.0001;
+
+// The solid angle density of reaching `direction` from `origin` by picking a point uniformly on
+// a rectangle of the given area.
+fn area_pdf_value(rect: &dyn Hittable, area: f64, origin: Point3, direction: Vec3) -> f64 {
+    match rect.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY) {
+        Some(rec) => {
+            let distance_squared = rec.get_t().powi(2) * direction.length_squared();
+            let cosine = (direction.dot(&rec.get_normal()) / direction.length()).abs();
+            distance_squared / (cosine * area)
+        }
+        None => 0.0,
+    }
+}
+
+#[derive(Clone)]
+pub struct XYRect {
+    x0: f64,
+    x1: f64,
+    y0: f64,
+    y1: f64,
+    k: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl XYRect {
+    /// The bounds may be given in either order.
+    #[inline(always)]
+    pub fn new(
+        x0: f64,
+        x1: f64,
+        y0: f64,
+        y1: f64,
+        k: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> XYRect {
+        XYRect {
+            x0: x0.min(x1),
+            x1: x0.max(x1),
+            y0: y0.min(y1),
+            y1: y0.max(y1),
+            k,
+            material,
+        }
+    }
+}
+
+impl Hittable for XYRect {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // A ray lying in the plane would give a NaN distance that passes every comparison.
+        if r.get_direction().get_z() == 0.0 {
+            return None;
+        }
+        let t = (self.k - r.get_origin().get_z()) / r.get_direction().get_z();
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let p = r.at(t);
+        if p.get_x() < self.x0 || p.get_x() > self.x1 || p.get_y() < self.y0 || p.get_y() > self.y1
+        {
+            return None;
+        }
+        let u = (p.get_x() - self.x0) / (self.x1 - self.x0);
+        let v = (p.get_y() - self.y0) / (self.y1 - self.y0);
+        Some(
+            HitRecord::from_outward_normal(
+                p,
+                t,
+                r,
+                Vec3::new(0.0, 0.0, 1.0),
+                self.material.clone(),
+            )
+            .with_uv((u, v))
+            .with_derivatives(
+                Vec3::new(self.x1 - self.x0, 0.0, 0.0),
+                Vec3::new(0.0, self.y1 - self.y0, 0.0),
+            ),
+        )
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let area = (self.x1 - self.x0) * (self.y1 - self.y0);
+        area_pdf_value(self, area, origin, direction)
+    }
+
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        Point3::new(
+            self.x0 + (u.0 * (self.x1 - self.x0)),
+            self.y0 + (u.1 * (self.y1 - self.y0)),
+            self.k,
+        ) - origin
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        Some(Box::new(AABB::new(
+            Point3::new(self.x0, self.y0, self.k - PADDING),
+            Point3::new(self.x1, self.y1, self.k + PADDING),
+        )))
+    }
+}
+
+#[derive(Clone)]
+pub struct XZRect {
+    x0: f64,
+    x1: f64,
+    z0: f64,
+    z1: f64,
+    k: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl XZRect {
+    /// The bounds may be given in either order.
+    #[inline(always)]
+    pub fn new(
+        x0: f64,
+        x1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> XZRect {
+        XZRect {
+            x0: x0.min(x1),
+            x1: x0.max(x1),
+            z0: z0.min(z1),
+            z1: z0.max(z1),
+            k,
+            material,
+        }
+    }
+}
+
+impl Hittable for XZRect {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if r.get_direction().get_y() == 0.0 {
+            return None;
+        }
+        let t = (self.k - r.get_origin().get_y()) / r.get_direction().get_y();
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let p = r.at(t);
+        if p.get_x() < self.x0 || p.get_x() > self.x1 || p.get_z() < self.z0 || p.get_z() > self.z1
+        {
+            return None;
+        }
+        let u = (p.get_x() - self.x0) / (self.x1 - self.x0);
+        let v = (p.get_z() - self.z0) / (self.z1 - self.z0);
+        Some(
+            HitRecord::from_outward_normal(
+                p,
+                t,
+                r,
+                Vec3::new(0.0, 1.0, 0.0),
+                self.material.clone(),
+            )
+            .with_uv((u, v))
+            .with_derivatives(
+                Vec3::new(self.x1 - self.x0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, self.z1 - self.z0),
+            ),
+        )
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let area = (self.x1 - self.x0) * (self.z1 - self.z0);
+        area_pdf_value(self, area, origin, direction)
+    }
+
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        Point3::new(
+            self.x0 + (u.0 * (self.x1 - self.x0)),
+            self.k,
+            self.z0 + (u.1 * (self.z1 - self.z0)),
+        ) - origin
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        Some(Box::new(AABB::new(
+            Point3::new(self.x0, self.k - PADDING, self.z0),
+            Point3::new(self.x1, self.k + PADDING, self.z1),
+        )))
+    }
+}
+
+#[derive(Clone)]
+pub struct YZRect {
+    y0: f64,
+    y1: f64,
+    z0: f64,
+    z1: f64,
+    k: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl YZRect {
+    /// The bounds may be given in either order.
+    #[inline(always)]
+    pub fn new(
+        y0: f64,
+        y1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> YZRect {
+        YZRect {
+            y0: y0.min(y1),
+            y1: y0.max(y1),
+            z0: z0.min(z1),
+            z1: z0.max(z1),
+            k,
+            material,
+        }
+    }
+}
+
+impl Hittable for YZRect {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if r.get_direction().get_x() == 0.0 {
+            return None;
+        }
+        let t = (self.k - r.get_origin().get_x()) / r.get_direction().get_x();
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let p = r.at(t);
+        if p.get_y() < self.y0 || p.get_y() > self.y1 || p.get_z() < self.z0 || p.get_z() > self.z1
+        {
+            return None;
+        }
+        let u = (p.get_y() - self.y0) / (self.y1 - self.y0);
+        let v = (p.get_z() - self.z0) / (self.z1 - self.z0);
+        Some(
+            HitRecord::from_outward_normal(
+                p,
+                t,
+                r,
+                Vec3::new(1.0, 0.0, 0.0),
+                self.material.clone(),
+            )
+            .with_uv((u, v))
+            .with_derivatives(
+                Vec3::new(0.0, self.y1 - self.y0, 0.0),
+                Vec3::new(0.0, 0.0, self.z1 - self.z0),
+            ),
+        )
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let area = (self.y1 - self.y0) * (self.z1 - self.z0);
+        area_pdf_value(self, area, origin, direction)
+    }
+
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        Point3::new(
+            self.k,
+            self.y0 + (u.0 * (self.y1 - self.y0)),
+            self.z0 + (u.1 * (self.z1 - self.z0)),
+        ) - origin
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        Some(Box::new(AABB::new(
+            Point3::new(self.k - PADDING, self.y0, self.z0),
+            Point3::new(self.k + PADDING, self.y1, self.z1),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::PI;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+
+    #[test]
+    fn test_xz_rect_hit() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let floor = XZRect::new(-1.0, 1.0, -1.0, 1.0, 0.0, material);
+        let r = Ray::new(Point3::new(0.5, 2.0, 0.5), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let rec = floor.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_t(), 2.0);
+        assert_eq!(rec.get_normal(), Vec3::new(0.0, 1.0, 0.0));
+        assert!(rec.is_front_face());
+        assert_eq!(rec.get_uv(), (0.75, 0.75));
+        assert_eq!(rec.get_dpdu(), Vec3::new(2.0, 0.0, 0.0));
+
+        let r = Ray::new(Point3::new(1.5, 2.0, 0.5), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(floor.hit(&r, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_pdf_integrates_to_one() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let ceiling = XZRect::new(-1.0, 1.0, -2.0, 1.0, 1.0, material);
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        for i in 0..4 {
+            for j in 0..4 {
+                let d = ceiling.random_direction(origin, (i as f64 / 4.0, j as f64 / 4.0));
+                assert!(ceiling.pdf_value(origin, d) > 0.0);
+            }
+        }
+        // Midpoint rule over the upper hemisphere in (phi, theta).
+        let n = 400;
+        let mut integral = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let phi = 2.0 * PI * (i as f64 + 0.5) / n as f64;
+                let theta = 0.5 * PI * (j as f64 + 0.5) / n as f64;
+                let (sin_theta, cos_theta) = theta.sin_cos();
+                let d = Vec3::new(phi.cos() * sin_theta, cos_theta, phi.sin() * sin_theta);
+                integral += ceiling.pdf_value(origin, d) * sin_theta * PI * PI / (n * n) as f64;
+            }
+        }
+        assert!((integral - 1.0).abs() < 1e-2, "{}", integral);
+    }
+
+    #[test]
+    fn test_padded_bounding_box_is_hit() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let wall = YZRect::new(0.0, 1.0, 0.0, 1.0, 3.0, material);
+        let bbox = wall.bounding_box(0.0, 1.0).unwrap();
+        let r = Ray::new(Point3::new(0.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(bbox.hit(&r, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn test_ray_in_plane_misses() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let floor = XZRect::new(-1.0, 1.0, -1.0, 1.0, 0.0, material);
+        let r = Ray::new(Point3::new(-2.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(floor.hit(&r, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_bounds_in_any_order() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let wall = XYRect::new(1.0, -1.0, 2.0, 0.0, 0.0, material);
+        let r = Ray::new(Point3::new(0.5, 1.5, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let rec = wall.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_uv(), (0.75, 0.75));
+    }
+}
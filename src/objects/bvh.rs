@@ -0,0 +1,688 @@
+use std::boxed::Box;
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::aabb::AABB;
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::hittable::{HitRecord, Hittable};
+use crate::objects::hittable_list::HittableList;
+
+// Relative costs used by the surface area heuristic. A primitive intersection is the unit of
+// cost, stepping through an interior node is considered much cheaper.
+const TRAVERSAL_COST: f64 = 0.125;
+const INTERSECTION_COST: f64 = 1.0;
+
+// Below this many primitives subtrees are built on the current thread, rayon's task overhead
+// outweighs the work.
+const PARALLEL_THRESHOLD: usize = 4096;
+
+// Past this depth the builder only uses median splits, which halve the primitive count every
+// level. This bounds the tree depth, and so the traversal stack, to 32 + log2(primitive count).
+const MAX_SAH_DEPTH: usize = 32;
+const TRAVERSAL_STACK_SIZE: usize = 96;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitMethod {
+    /// Split at the median centroid along the longest axis of the node.
+    Median,
+    /// Binned surface area heuristic, evaluating `bins - 1` candidate planes on every axis.
+    Sah { bins: usize },
+}
+
+impl Default for SplitMethod {
+    fn default() -> SplitMethod {
+        SplitMethod::Sah { bins: 16 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BvhStats {
+    pub primitive_count: usize,
+    pub interior_count: usize,
+    pub leaf_count: usize,
+    pub max_depth: usize,
+    pub max_leaf_size: usize,
+    pub sah_cost: f64,
+}
+
+struct BuildPrimitive {
+    index: usize,
+    bounds: AABB,
+    centroid: Point3,
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    bounds: Option<AABB>,
+    count: usize,
+}
+
+enum BuildNode {
+    Leaf {
+        bounds: AABB,
+        start: usize,
+        count: usize,
+    },
+    Interior {
+        bounds: AABB,
+        axis: usize,
+        children: Box<(BuildNode, BuildNode)>,
+    },
+}
+
+// A node of the flattened tree. The first child of an interior node directly follows it in the
+// node array, `offset` holds the index of the second child. For leaves `offset` is the first of
+// `count` entries in the primitive index array.
+#[derive(Clone, Copy, Debug)]
+struct BvhNode {
+    bounds: AABB,
+    offset: usize,
+    count: usize,
+    axis: usize,
+}
+
+/// Bounding volume hierarchy over primitives identified only by their index, stored as a
+/// contiguous depth-first array of nodes.
+pub struct LinearBvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>,
+}
+
+impl LinearBvh {
+    pub fn new(bounds: &[AABB], split_method: SplitMethod, max_leaf_size: usize) -> LinearBvh {
+        if bounds.is_empty() {
+            return LinearBvh {
+                nodes: Vec::new(),
+                indices: Vec::new(),
+            };
+        }
+
+        let mut primitives: Vec<BuildPrimitive> = bounds
+            .iter()
+            .enumerate()
+            .map(|(index, b)| BuildPrimitive {
+                index,
+                bounds: *b,
+                centroid: b.get_centroid(),
+            })
+            .collect();
+        let root = LinearBvh::build(&mut primitives, 0, 0, split_method, max_leaf_size.max(1));
+
+        let mut nodes = Vec::with_capacity(2 * bounds.len());
+        LinearBvh::flatten(&root, &mut nodes);
+        LinearBvh {
+            nodes,
+            indices: primitives.iter().map(|p| p.index).collect(),
+        }
+    }
+
+    pub fn get_bounds(&self) -> Option<AABB> {
+        self.nodes.first().map(|n| n.bounds)
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Finds the closest primitive hit along `r`. `hit_primitive` is called with the index of a
+    /// candidate primitive and the current closest distance, which it should use as its `t_max`.
+    pub fn hit<F>(&self, r: &Ray, t_min: f64, t_max: f64, mut hit_primitive: F) -> Option<HitRecord>
+    where
+        F: FnMut(usize, f64) -> Option<HitRecord>,
+    {
+        self.closest(r, t_min, t_max, |index, closest| {
+            hit_primitive(index, closest).map(|rec| (rec.get_t(), rec))
+        })
+        .map(|(_, rec)| rec)
+    }
+
+    /// Like `hit`, for primitives that report any result along with its distance, so callers
+    /// can put off building a full record until the closest hit is known.
+    pub fn closest<T, F>(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut hit_primitive: F,
+    ) -> Option<(f64, T)>
+    where
+        F: FnMut(usize, f64) -> Option<(f64, T)>,
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let origin = r.get_origin();
+        let d = r.get_direction();
+        let inv_dir = Vec3::new(1.0 / d.get_x(), 1.0 / d.get_y(), 1.0 / d.get_z());
+        let dir_is_neg = [
+            inv_dir.get_x() < 0.0,
+            inv_dir.get_y() < 0.0,
+            inv_dir.get_z() < 0.0,
+        ];
+
+        let mut stack = [0_usize; TRAVERSAL_STACK_SIZE];
+        let mut stack_len = 0;
+        let mut current = 0;
+        let mut closest = t_max;
+        let mut result = None;
+        loop {
+            let node = &self.nodes[current];
+            if node.bounds.hit_inverse(origin, inv_dir, t_min, closest) {
+                if node.count > 0 {
+                    for &index in &self.indices[node.offset..(node.offset + node.count)] {
+                        if let Some((t, hit)) = hit_primitive(index, closest) {
+                            closest = t;
+                            result = Some((t, hit));
+                        }
+                    }
+                } else {
+                    // Visit the child nearer to the ray origin first so the far one can be
+                    // culled by the closest hit found so far.
+                    let (near, far) = if dir_is_neg[node.axis] {
+                        (node.offset, current + 1)
+                    } else {
+                        (current + 1, node.offset)
+                    };
+                    stack[stack_len] = far;
+                    stack_len += 1;
+                    current = near;
+                    continue;
+                }
+            }
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            current = stack[stack_len];
+        }
+        result
+    }
+
+    /// Reports whether any primitive is hit along `r` between `t_min` and `t_max`, stopping at
+    /// the first one found. `hit_primitive` is called with the index of a candidate primitive.
+    pub fn occluded<F>(&self, r: &Ray, t_min: f64, t_max: f64, mut hit_primitive: F) -> bool
+    where
+        F: FnMut(usize) -> bool,
+    {
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let origin = r.get_origin();
+        let d = r.get_direction();
+        let inv_dir = Vec3::new(1.0 / d.get_x(), 1.0 / d.get_y(), 1.0 / d.get_z());
+
+        let mut stack = [0_usize; TRAVERSAL_STACK_SIZE];
+        let mut stack_len = 0;
+        let mut current = 0;
+        loop {
+            let node = &self.nodes[current];
+            if node.bounds.hit_inverse(origin, inv_dir, t_min, t_max) {
+                if node.count > 0 {
+                    if self.indices[node.offset..(node.offset + node.count)]
+                        .iter()
+                        .any(|&index| hit_primitive(index))
+                    {
+                        return true;
+                    }
+                } else {
+                    // Any hit will do, so the order children are visited in doesn't matter.
+                    stack[stack_len] = node.offset;
+                    stack_len += 1;
+                    current += 1;
+                    continue;
+                }
+            }
+            if stack_len == 0 {
+                return false;
+            }
+            stack_len -= 1;
+            current = stack[stack_len];
+        }
+    }
+
+    pub fn stats(&self) -> BvhStats {
+        let mut stats = BvhStats::default();
+        if let Some(bounds) = self.get_bounds() {
+            let root_area = bounds.surface_area().max(f64::EPSILON);
+            self.accumulate_stats(0, &mut stats, 1, root_area);
+        }
+        stats
+    }
+
+    fn accumulate_stats(&self, current: usize, stats: &mut BvhStats, depth: usize, root_area: f64) {
+        let node = &self.nodes[current];
+        let area_ratio = node.bounds.surface_area() / root_area;
+        stats.max_depth = stats.max_depth.max(depth);
+        if node.count > 0 {
+            stats.leaf_count += 1;
+            stats.primitive_count += node.count;
+            stats.max_leaf_size = stats.max_leaf_size.max(node.count);
+            stats.sah_cost += INTERSECTION_COST * node.count as f64 * area_ratio;
+        } else {
+            stats.interior_count += 1;
+            stats.sah_cost += TRAVERSAL_COST * area_ratio;
+            self.accumulate_stats(current + 1, stats, depth + 1, root_area);
+            self.accumulate_stats(node.offset, stats, depth + 1, root_area);
+        }
+    }
+
+    fn flatten(node: &BuildNode, nodes: &mut Vec<BvhNode>) -> usize {
+        let index = nodes.len();
+        match node {
+            BuildNode::Leaf {
+                bounds,
+                start,
+                count,
+            } => nodes.push(BvhNode {
+                bounds: *bounds,
+                offset: *start,
+                count: *count,
+                axis: 0,
+            }),
+            BuildNode::Interior {
+                bounds,
+                axis,
+                children,
+            } => {
+                nodes.push(BvhNode {
+                    bounds: *bounds,
+                    offset: 0,
+                    count: 0,
+                    axis: *axis,
+                });
+                LinearBvh::flatten(&children.0, nodes);
+                nodes[index].offset = LinearBvh::flatten(&children.1, nodes);
+            }
+        }
+        index
+    }
+
+    fn build(
+        primitives: &mut [BuildPrimitive],
+        start: usize,
+        depth: usize,
+        split_method: SplitMethod,
+        max_leaf_size: usize,
+    ) -> BuildNode {
+        let bounds = primitives
+            .iter()
+            .map(|p| p.bounds)
+            .reduce(AABB::surrounding_box)
+            .unwrap();
+        let leaf = BuildNode::Leaf {
+            bounds,
+            start,
+            count: primitives.len(),
+        };
+        if primitives.len() == 1 {
+            return leaf;
+        }
+
+        let centroid_bounds = primitives
+            .iter()
+            .map(|p| AABB::from_point(p.centroid))
+            .reduce(AABB::surrounding_box)
+            .unwrap();
+        let split_method = if depth >= MAX_SAH_DEPTH {
+            SplitMethod::Median
+        } else {
+            split_method
+        };
+        let (axis, mid) = match split_method {
+            SplitMethod::Median => {
+                if primitives.len() <= max_leaf_size {
+                    return leaf;
+                }
+                let axis = centroid_bounds.longest_axis();
+                (axis, LinearBvh::partition_median(primitives, axis))
+            }
+            SplitMethod::Sah { bins } => {
+                match LinearBvh::partition_sah(primitives, &bounds, &centroid_bounds, bins.max(2)) {
+                    Some(split) => split,
+                    None if primitives.len() <= max_leaf_size => return leaf,
+                    None => {
+                        let axis = centroid_bounds.longest_axis();
+                        (axis, LinearBvh::partition_median(primitives, axis))
+                    }
+                }
+            }
+        };
+
+        let (lower, upper) = primitives.split_at_mut(mid);
+        let children = if lower.len() + upper.len() > PARALLEL_THRESHOLD {
+            rayon::join(
+                || LinearBvh::build(lower, start, depth + 1, split_method, max_leaf_size),
+                || LinearBvh::build(upper, start + mid, depth + 1, split_method, max_leaf_size),
+            )
+        } else {
+            (
+                LinearBvh::build(lower, start, depth + 1, split_method, max_leaf_size),
+                LinearBvh::build(upper, start + mid, depth + 1, split_method, max_leaf_size),
+            )
+        };
+        BuildNode::Interior {
+            bounds,
+            axis,
+            children: Box::new(children),
+        }
+    }
+
+    fn partition_median(primitives: &mut [BuildPrimitive], axis: usize) -> usize {
+        let mid = primitives.len() / 2;
+        primitives.select_nth_unstable_by(mid, |a, b| {
+            a.centroid[axis]
+                .partial_cmp(&b.centroid[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+        mid
+    }
+
+    // Returns the axis and split point of the cheapest binned SAH partition, or `None` when
+    // keeping all primitives in a single leaf is cheaper or the centroids cannot be separated.
+    fn partition_sah(
+        primitives: &mut [BuildPrimitive],
+        bbox: &AABB,
+        centroid_bounds: &AABB,
+        bins: usize,
+    ) -> Option<(usize, usize)> {
+        let extent = centroid_bounds.get_extent();
+        let best = (0..3)
+            .filter(|&axis| extent[axis] > 0.0)
+            .filter_map(|axis| {
+                LinearBvh::best_split_on_axis(primitives, centroid_bounds, axis, bins)
+                    .map(|(bin, cost)| (axis, bin, cost))
+            })
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal));
+        let (axis, split_bin, cost) = best?;
+
+        let leaf_cost = INTERSECTION_COST * primitives.len() as f64;
+        let split_cost = TRAVERSAL_COST + (cost / bbox.surface_area().max(f64::EPSILON));
+        if split_cost >= leaf_cost {
+            return None;
+        }
+
+        let mut mid = 0;
+        for i in 0..primitives.len() {
+            if LinearBvh::bin_index(primitives[i].centroid, centroid_bounds, axis, bins)
+                <= split_bin
+            {
+                primitives.swap(i, mid);
+                mid += 1;
+            }
+        }
+        if mid == 0 || mid == primitives.len() {
+            None
+        } else {
+            Some((axis, mid))
+        }
+    }
+
+    // Finds the cheapest plane between bins on a single axis. The returned cost is the
+    // unnormalized `A_left * N_left + A_right * N_right` scaled by the intersection cost.
+    fn best_split_on_axis(
+        primitives: &[BuildPrimitive],
+        centroid_bounds: &AABB,
+        axis: usize,
+        bins: usize,
+    ) -> Option<(usize, f64)> {
+        let empty = Bin {
+            bounds: None,
+            count: 0,
+        };
+        let mut buckets = vec![empty; bins];
+        for p in primitives {
+            let bucket =
+                &mut buckets[LinearBvh::bin_index(p.centroid, centroid_bounds, axis, bins)];
+            bucket.count += 1;
+            bucket.bounds = Some(LinearBvh::merge(bucket.bounds, p.bounds));
+        }
+
+        // Sweep from the right first so every plane knows the area and count on its far side.
+        let mut right_area = vec![0.0; bins];
+        let mut right_count = vec![0; bins];
+        let mut acc = empty;
+        for i in (1..bins).rev() {
+            acc.count += buckets[i].count;
+            if let Some(b) = buckets[i].bounds {
+                acc.bounds = Some(LinearBvh::merge(acc.bounds, b));
+            }
+            right_count[i - 1] = acc.count;
+            right_area[i - 1] = acc.bounds.map_or(0.0, |b| b.surface_area());
+        }
+
+        let mut best: Option<(usize, f64)> = None;
+        let mut acc = empty;
+        for i in 0..(bins - 1) {
+            acc.count += buckets[i].count;
+            if let Some(b) = buckets[i].bounds {
+                acc.bounds = Some(LinearBvh::merge(acc.bounds, b));
+            }
+            if acc.count == 0 || right_count[i] == 0 {
+                continue;
+            }
+            let left_area = acc.bounds.map_or(0.0, |b| b.surface_area());
+            let cost = INTERSECTION_COST
+                * ((left_area * acc.count as f64) + (right_area[i] * right_count[i] as f64));
+            let better = match best {
+                Some((_, best_cost)) => cost < best_cost,
+                None => true,
+            };
+            if better {
+                best = Some((i, cost));
+            }
+        }
+        best
+    }
+
+    #[inline(always)]
+    fn bin_index(centroid: Point3, centroid_bounds: &AABB, axis: usize, bins: usize) -> usize {
+        let low = centroid_bounds.get_low()[axis];
+        let extent = centroid_bounds.get_extent()[axis];
+        let b = (bins as f64 * ((centroid[axis] - low) / extent)) as usize;
+        b.min(bins - 1)
+    }
+
+    #[inline(always)]
+    fn merge(a: Option<AABB>, b: AABB) -> AABB {
+        match a {
+            Some(a) => AABB::surrounding_box(a, b),
+            None => b,
+        }
+    }
+}
+
+pub struct Bvh {
+    tree: LinearBvh,
+    primitives: Vec<Arc<dyn Hittable + Sync + Send>>,
+}
+
+impl Bvh {
+    pub fn new(objects: Vec<Box<dyn Hittable + Sync + Send>>, time0: f64, time1: f64) -> Bvh {
+        Bvh::with_split_method(objects, time0, time1, SplitMethod::default(), 4)
+    }
+
+    pub fn with_split_method(
+        objects: Vec<Box<dyn Hittable + Sync + Send>>,
+        time0: f64,
+        time1: f64,
+        split_method: SplitMethod,
+        max_leaf_size: usize,
+    ) -> Bvh {
+        let objects = objects.into_iter().map(Arc::from).collect();
+        Bvh::from_shared(objects, time0, time1, split_method, max_leaf_size)
+    }
+
+    pub fn from_list(list: &HittableList, time0: f64, time1: f64) -> Bvh {
+        let objects = list.iter().cloned().collect();
+        Bvh::from_shared(objects, time0, time1, SplitMethod::default(), 4)
+    }
+
+    pub fn from_shared(
+        primitives: Vec<Arc<dyn Hittable + Sync + Send>>,
+        time0: f64,
+        time1: f64,
+        split_method: SplitMethod,
+        max_leaf_size: usize,
+    ) -> Bvh {
+        let bounds: Vec<AABB> = primitives
+            .iter()
+            .map(|object| {
+                let bbox = object
+                    .bounding_box(time0, time1)
+                    .expect("No bounding box in Bvh constructor");
+                AABB::new(bbox.get_lower(), bbox.get_upper())
+            })
+            .collect();
+        Bvh {
+            tree: LinearBvh::new(&bounds, split_method, max_leaf_size),
+            primitives,
+        }
+    }
+
+    pub fn stats(&self) -> BvhStats {
+        self.tree.stats()
+    }
+}
+
+impl Hittable for Bvh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.tree.hit(r, t_min, t_max, |index, closest| {
+            self.primitives[index]
+                .hit(r, t_min, closest)
+                .map(|rec| rec.with_object_id(index))
+        })
+    }
+
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.tree.occluded(r, t_min, t_max, |index| {
+            self.primitives[index].occluded(r, t_min, t_max)
+        })
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        self.tree
+            .get_bounds()
+            .map(|b| Box::new(b) as Box<dyn BoundingBox>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+    use crate::objects::sphere::Sphere;
+    use crate::vec3::{Point3, Vec3};
+
+    fn sphere_row(count: usize) -> Vec<Box<dyn Hittable + Sync + Send>> {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        (0..count)
+            .map(|i| {
+                Box::new(Sphere::new_immobile(
+                    Point3::new(i as f64 * 3.0, 0.0, 0.0),
+                    1.0,
+                    material.clone(),
+                )) as Box<dyn Hittable + Sync + Send>
+            })
+            .collect()
+    }
+
+    fn check_against_linear_search(bvh: &Bvh, count: usize) {
+        let spheres = sphere_row(count);
+        for i in 0..count {
+            let r = Ray::new(
+                Point3::new(i as f64 * 3.0 + 0.25, 10.0, 0.0),
+                Vec3::new(0.0, -1.0, 0.0),
+                0.0,
+            );
+            let expected = spheres
+                .iter()
+                .filter_map(|s| s.hit(&r, 0.001, f64::INFINITY))
+                .map(|rec| rec.get_t())
+                .fold(f64::INFINITY, f64::min);
+            let actual = bvh.hit(&r, 0.001, f64::INFINITY).unwrap();
+            assert_eq!(actual.get_t(), expected);
+        }
+    }
+
+    #[test]
+    fn test_hit_matches_linear_search() {
+        let bvh = Bvh::new(sphere_row(37), 0.0, 1.0);
+        check_against_linear_search(&bvh, 37);
+    }
+
+    #[test]
+    fn test_median_hit_matches_linear_search() {
+        let bvh = Bvh::with_split_method(sphere_row(37), 0.0, 1.0, SplitMethod::Median, 1);
+        check_against_linear_search(&bvh, 37);
+    }
+
+    #[test]
+    fn test_miss() {
+        let bvh = Bvh::new(sphere_row(8), 0.0, 1.0);
+        let r = Ray::new(Point3::new(1.5, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(bvh.hit(&r, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_occluded() {
+        let bvh = Bvh::new(sphere_row(16), 0.0, 1.0);
+        // Along the row every sphere is in the way, but only up to `t_max`.
+        let r = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(bvh.occluded(&r, 0.001, f64::INFINITY));
+        assert!(bvh.occluded(&r, 0.001, 4.5));
+        assert!(!bvh.occluded(&r, 0.001, 3.5));
+        let r = Ray::new(Point3::new(1.5, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(!bvh.occluded(&r, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn test_empty() {
+        let bvh = Bvh::new(Vec::new(), 0.0, 1.0);
+        let r = Ray::new(Point3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(bvh.hit(&r, 0.001, f64::INFINITY).is_none());
+        assert!(bvh.bounding_box(0.0, 1.0).is_none());
+        assert_eq!(bvh.stats(), BvhStats::default());
+    }
+
+    #[test]
+    fn test_ray_from_far_side() {
+        let bvh = Bvh::new(sphere_row(16), 0.0, 1.0);
+        let r = Ray::new(Point3::new(100.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        let rec = bvh.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_point(), Point3::new(46.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bvh = Bvh::new(sphere_row(4), 0.0, 1.0);
+        let bbox = bvh.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.get_lower(), Point3::new(-1.0, -1.0, -1.0));
+        assert_eq!(bbox.get_upper(), Point3::new(10.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_stats() {
+        let median = Bvh::with_split_method(sphere_row(64), 0.0, 1.0, SplitMethod::Median, 1);
+        let stats = median.stats();
+        assert_eq!(stats.primitive_count, 64);
+        assert_eq!(stats.leaf_count, 64);
+        assert_eq!(stats.interior_count, 63);
+        assert_eq!(stats.max_depth, 7);
+
+        let sah = Bvh::new(sphere_row(64), 0.0, 1.0).stats();
+        assert_eq!(sah.primitive_count, 64);
+        assert!(sah.sah_cost <= stats.sah_cost);
+    }
+}
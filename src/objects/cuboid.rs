@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::aabb::AABB;
+use crate::objects::aarect::{XYRect, XZRect, YZRect};
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::hittable::{HitRecord, Hittable};
+use crate::objects::hittable_list::HittableList;
+use crate::objects::material::Material;
+
+#[derive(Clone)]
+pub struct Cuboid {
+    box_min: Point3,
+    box_max: Point3,
+    sides: HittableList,
+}
+
+impl Cuboid {
+    /// A box spanning the opposite corners `p0` and `p1`, given in any order.
+    pub fn new(p0: Point3, p1: Point3, material: Arc<dyn Material + Sync + Send>) -> Cuboid {
+        let box_min = Point3::new(
+            p0.get_x().min(p1.get_x()),
+            p0.get_y().min(p1.get_y()),
+            p0.get_z().min(p1.get_z()),
+        );
+        let box_max = Point3::new(
+            p0.get_x().max(p1.get_x()),
+            p0.get_y().max(p1.get_y()),
+            p0.get_z().max(p1.get_z()),
+        );
+        let mut sides = HittableList::new();
+        let (x0, y0, z0) = (box_min.get_x(), box_min.get_y(), box_min.get_z());
+        let (x1, y1, z1) = (box_max.get_x(), box_max.get_y(), box_max.get_z());
+
+        sides.add(Arc::new(XYRect::new(x0, x1, y0, y1, z1, material.clone())));
+        sides.add(Arc::new(XYRect::new(x0, x1, y0, y1, z0, material.clone())));
+
+        sides.add(Arc::new(XZRect::new(x0, x1, z0, z1, y1, material.clone())));
+        sides.add(Arc::new(XZRect::new(x0, x1, z0, z1, y0, material.clone())));
+
+        sides.add(Arc::new(YZRect::new(y0, y1, z0, z1, x1, material.clone())));
+        sides.add(Arc::new(YZRect::new(y0, y1, z0, z1, x0, material)));
+
+        Cuboid {
+            box_min,
+            box_max,
+            sides,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_min(&self) -> Point3 {
+        self.box_min
+    }
+
+    #[inline(always)]
+    pub fn get_max(&self) -> Point3 {
+        self.box_max
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.sides.hit(r, t_min, t_max)
+    }
+
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.sides.occluded(r, t_min, t_max)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.sides.pdf_value(origin, direction)
+    }
+
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        self.sides.random_direction(origin, u)
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        Some(Box::new(AABB::new(self.box_min, self.box_max)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+
+    fn gray_cuboid(p0: Point3, p1: Point3) -> Cuboid {
+        Cuboid::new(p0, p1, Arc::new(Lambertian::new(Color::new(127, 127, 127))))
+    }
+
+    #[test]
+    fn test_hit_from_outside() {
+        let cuboid = gray_cuboid(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+        let r = Ray::new(Point3::new(0.5, 1.0, 10.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let rec = cuboid.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_t(), 7.0);
+        assert_eq!(rec.get_point(), Point3::new(0.5, 1.0, 3.0));
+        assert_eq!(rec.get_normal(), Vec3::new(0.0, 0.0, 1.0));
+        assert!(rec.is_front_face());
+
+        let inside = Ray::new(Point3::new(0.5, 1.0, 1.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let rec = cuboid.hit(&inside, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_normal(), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!rec.is_front_face());
+
+        let miss = Ray::new(Point3::new(2.0, 1.0, 10.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(cuboid.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_corners_in_any_order() {
+        let cuboid = gray_cuboid(Point3::new(1.0, 0.0, 3.0), Point3::new(0.0, 2.0, 0.0));
+        assert_eq!(cuboid.get_min(), Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(cuboid.get_max(), Point3::new(1.0, 2.0, 3.0));
+        let bbox = cuboid.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.get_lower(), Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(bbox.get_upper(), Point3::new(1.0, 2.0, 3.0));
+
+        let r = Ray::new(Point3::new(0.5, 1.0, 10.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert_eq!(cuboid.hit(&r, 0.001, f64::INFINITY).unwrap().get_t(), 7.0);
+    }
+}
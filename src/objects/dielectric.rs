@@ -2,9 +2,10 @@ use std::ops::Neg;
 
 use crate::color::Color;
 use crate::ray::Ray;
+use crate::vec3::Vec3;
 
 use crate::objects::hittable::HitRecord;
-use crate::objects::material::Material;
+use crate::objects::material::{Material, ScatterRecord};
 
 use rand::random;
 
@@ -29,7 +30,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
+    fn sample(&self, ray_in: &Ray, record: &HitRecord) -> Option<ScatterRecord> {
         let refraction_ratio = if record.is_front_face() {
             1.0 / self.index_of_refraction
         } else {
@@ -45,9 +46,14 @@ impl Material for Dielectric {
         } else {
             unit_direction.refract(&record.get_normal(), refraction_ratio)
         };
-        Some((
-            Color::new(255, 255, 255),
-            Ray::new(record.get_point(), direction, ray_in.get_time()),
-        ))
+        Some(ScatterRecord::Specular {
+            attenuation: Color::new(255, 255, 255),
+            ray: Ray::new(record.get_point(), direction, ray_in.get_time()),
+        })
+    }
+
+    // Clear glass passes all light one way or another.
+    fn albedo(&self, _record: &HitRecord) -> Vec3 {
+        Vec3::new(1.0, 1.0, 1.0)
     }
 }
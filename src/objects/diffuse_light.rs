@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::texture::Texture;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::hittable::HitRecord;
+use crate::objects::material::{Material, ScatterRecord};
+
+/// Emits light equally in every direction and reflects none, turning any shape into a lamp.
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture + Sync + Send>,
+    intensity: f64,
+}
+
+impl DiffuseLight {
+    #[inline(always)]
+    pub fn new(color: Color, intensity: f64) -> DiffuseLight {
+        DiffuseLight::from_texture(Arc::new(SolidColor::new(color)), intensity)
+    }
+
+    #[inline(always)]
+    pub fn from_texture(emit: Arc<dyn Texture + Sync + Send>, intensity: f64) -> DiffuseLight {
+        DiffuseLight { emit, intensity }
+    }
+
+    #[inline(always)]
+    pub fn get_intensity(&self) -> f64 {
+        self.intensity
+    }
+}
+
+impl Material for DiffuseLight {
+    fn sample(&self, _ray_in: &Ray, _record: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: Point3) -> Vec3 {
+        self.intensity * Vec3::from(self.emit.value(u, v, p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::objects::aarect::XZRect;
+    use crate::objects::hittable::Hittable;
+
+    #[test]
+    fn test_emits_and_absorbs() {
+        let light = Arc::new(DiffuseLight::new(Color::new(255, 255, 255), 15.0));
+        let lamp = XZRect::new(-1.0, 1.0, -1.0, 1.0, 2.0, light);
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        let rec = lamp.hit(&r, 0.001, f64::INFINITY).unwrap();
+        let material = rec.get_material();
+        let (u, v) = rec.get_uv();
+        assert_eq!(
+            material.emitted(u, v, rec.get_point()),
+            Vec3::new(15.0, 15.0, 15.0)
+        );
+        assert!(material.sample(&r, &rec).is_none());
+    }
+}
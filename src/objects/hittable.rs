@@ -2,9 +2,11 @@ use std::boxed::Box;
 use std::option::Option;
 //use std::iter::Iterator;
 use std::sync::Arc;
-use std::vec::Vec;
 
+use crate::color::Color;
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::transform::Transform;
 use crate::vec3::{Point3, Vec3};
 
 use crate::objects::bounding_box::BoundingBox;
@@ -14,6 +16,13 @@ use crate::objects::material::Material;
 pub struct HitRecord {
     point: Point3,
     normal: Vec3,
+    geometric_normal: Vec3,
+    barycentrics: Vec3,
+    uv: (f64, f64),
+    dpdu: Vec3,
+    dpdv: Vec3,
+    vertex_color: Option<Color>,
+    object_id: Option<usize>,
     material: Arc<dyn Material>,
     t: f64,
     front_face: bool,
@@ -31,6 +40,13 @@ impl HitRecord {
         HitRecord {
             point,
             normal,
+            geometric_normal: normal,
+            barycentrics: Vec3::new(0.0, 0.0, 0.0),
+            uv: (0.0, 0.0),
+            dpdu: Vec3::new(0.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 0.0, 0.0),
+            vertex_color: None,
+            object_id: None,
             material,
             t,
             front_face,
@@ -53,6 +69,66 @@ impl HitRecord {
         HitRecord::new(point, normal, material, t, front_face)
     }
 
+    // Replaces the shading normal, keeping the geometric normal used to decide front_face.
+    // The shading normal is flipped onto the same side as the geometric one.
+    #[inline(always)]
+    pub fn with_shading_normal(mut self, outward_normal: Vec3) -> HitRecord {
+        self.normal = if self.front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_barycentrics(mut self, barycentrics: Vec3) -> HitRecord {
+        self.barycentrics = barycentrics;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_uv(mut self, uv: (f64, f64)) -> HitRecord {
+        self.uv = uv;
+        self
+    }
+
+    /// Sets how the surface point moves with `u` and `v`. Their lengths matter, they are not
+    /// normalized.
+    #[inline(always)]
+    pub fn with_derivatives(mut self, dpdu: Vec3, dpdv: Vec3) -> HitRecord {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_vertex_color(mut self, vertex_color: Color) -> HitRecord {
+        self.vertex_color = Some(vertex_color);
+        self
+    }
+
+    /// Marks which object was hit. Lists and hierarchies stamp the index of their child, so
+    /// the outermost one decides.
+    #[inline(always)]
+    pub fn with_object_id(mut self, object_id: usize) -> HitRecord {
+        self.object_id = Some(object_id);
+        self
+    }
+
+    /// Moves a record found in an object's own space into the space `transform` maps it to.
+    /// Orientation is preserved, so `front_face` still holds.
+    pub fn transformed(mut self, transform: &Transform) -> HitRecord {
+        self.point = transform.transform_point(self.point);
+        self.normal = transform.transform_normal(self.normal).unit_vector();
+        self.geometric_normal = transform
+            .transform_normal(self.geometric_normal)
+            .unit_vector();
+        self.dpdu = transform.transform_vector(self.dpdu);
+        self.dpdv = transform.transform_vector(self.dpdv);
+        self
+    }
+
     #[inline(always)]
     pub fn get_point(&self) -> Point3 {
         self.point
@@ -63,6 +139,50 @@ impl HitRecord {
         self.normal
     }
 
+    #[inline(always)]
+    pub fn get_geometric_normal(&self) -> Vec3 {
+        self.geometric_normal
+    }
+
+    #[inline(always)]
+    pub fn get_barycentrics(&self) -> Vec3 {
+        self.barycentrics
+    }
+
+    /// Surface texture coordinates, `(0, 0)` for shapes that don't define them.
+    #[inline(always)]
+    pub fn get_uv(&self) -> (f64, f64) {
+        self.uv
+    }
+
+    #[inline(always)]
+    pub fn get_dpdu(&self) -> Vec3 {
+        self.dpdu
+    }
+
+    #[inline(always)]
+    pub fn get_dpdv(&self) -> Vec3 {
+        self.dpdv
+    }
+
+    /// The shading normal as `w` with the tangent following `dpdu`, or an arbitrary tangent for
+    /// surfaces without derivatives.
+    #[inline(always)]
+    pub fn get_tangent_frame(&self) -> Onb {
+        Onb::from_w_and_tangent(self.normal, self.dpdu)
+    }
+
+    #[inline(always)]
+    pub fn get_vertex_color(&self) -> Option<Color> {
+        self.vertex_color
+    }
+
+    /// The index of the hit object in the outermost list or hierarchy, if there is one.
+    #[inline(always)]
+    pub fn get_object_id(&self) -> Option<usize> {
+        self.object_id
+    }
+
     #[inline(always)]
     pub fn get_material(&self) -> Arc<dyn Material> {
         self.material.clone()
@@ -82,4 +202,22 @@ impl HitRecord {
 pub trait Hittable {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
     fn bounding_box(&self, time0: f64, time1: f64) -> Option<Box<dyn BoundingBox>>;
+
+    /// Whether anything is hit between `t_min` and `t_max`. Shadow rays only need a yes or no,
+    /// so aggregates override this to stop at the first hit instead of finding the closest.
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.hit(r, t_min, t_max).is_some()
+    }
+
+    /// The density, per unit solid angle around `origin`, of `random_direction` picking
+    /// `direction`. Only objects that are sampled as lights need this.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Maps two uniform numbers to a direction from `origin` towards the object, not
+    /// necessarily of unit length.
+    fn random_direction(&self, _origin: Point3, _u: (f64, f64)) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
 }
@@ -0,0 +1,189 @@
+use std::boxed::Box;
+use std::iter::FromIterator;
+use std::slice::Iter;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::aabb::AABB;
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::hittable::{HitRecord, Hittable};
+
+#[derive(Clone, Default)]
+pub struct HittableList {
+    objects: Vec<Arc<dyn Hittable + Sync + Send>>,
+}
+
+impl HittableList {
+    #[inline(always)]
+    pub fn new() -> HittableList {
+        HittableList {
+            objects: Vec::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn add(&mut self, object: Arc<dyn Hittable + Sync + Send>) {
+        self.objects.push(object);
+    }
+
+    #[inline(always)]
+    pub fn remove(&mut self, index: usize) -> Arc<dyn Hittable + Sync + Send> {
+        self.objects.remove(index)
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Option<&Arc<dyn Hittable + Sync + Send>> {
+        self.objects.get(index)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> Iter<'_, Arc<dyn Hittable + Sync + Send>> {
+        self.objects.iter()
+    }
+
+    #[inline(always)]
+    pub fn into_objects(self) -> Vec<Arc<dyn Hittable + Sync + Send>> {
+        self.objects
+    }
+}
+
+impl Hittable for HittableList {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut closest = t_max;
+        let mut result = None;
+        for (index, object) in self.objects.iter().enumerate() {
+            if let Some(rec) = object.hit(r, t_min, closest) {
+                closest = rec.get_t();
+                result = Some(rec.with_object_id(index));
+            }
+        }
+        result
+    }
+
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.occluded(r, t_min, t_max))
+    }
+
+    // Every object is equally likely to be sampled.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .objects
+            .iter()
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+        total / self.objects.len() as f64
+    }
+
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let scaled = u.0 * self.objects.len() as f64;
+        let index = (scaled as usize).min(self.objects.len() - 1);
+        self.objects[index].random_direction(origin, (scaled - index as f64, u.1))
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Box<dyn BoundingBox>> {
+        let mut output: Option<AABB> = None;
+        for object in &self.objects {
+            let bbox = object.bounding_box(time0, time1)?;
+            let bbox = AABB::new(bbox.get_lower(), bbox.get_upper());
+            output = Some(match output {
+                Some(acc) => AABB::surrounding_box(acc, bbox),
+                None => bbox,
+            });
+        }
+        output.map(|b| Box::new(b) as Box<dyn BoundingBox>)
+    }
+}
+
+impl FromIterator<Arc<dyn Hittable + Sync + Send>> for HittableList {
+    fn from_iter<I: IntoIterator<Item = Arc<dyn Hittable + Sync + Send>>>(iter: I) -> Self {
+        HittableList {
+            objects: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Arc<dyn Hittable + Sync + Send>> for HittableList {
+    fn extend<I: IntoIterator<Item = Arc<dyn Hittable + Sync + Send>>>(&mut self, iter: I) {
+        self.objects.extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for &'a HittableList {
+    type Item = &'a Arc<dyn Hittable + Sync + Send>;
+    type IntoIter = Iter<'a, Arc<dyn Hittable + Sync + Send>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.objects.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+    use crate::objects::sphere::Sphere;
+
+    fn sphere_at(x: f64) -> Arc<dyn Hittable + Sync + Send> {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        Arc::new(Sphere::new_immobile(
+            Point3::new(x, 0.0, 0.0),
+            1.0,
+            material,
+        ))
+    }
+
+    #[test]
+    fn test_closest_hit() {
+        let mut world = HittableList::new();
+        world.add(sphere_at(6.0));
+        world.add(sphere_at(3.0));
+        world.add(sphere_at(9.0));
+        let r = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let rec = world.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_point(), Point3::new(2.0, 0.0, 0.0));
+
+        world.remove(1);
+        let rec = world.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_point(), Point3::new(5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_nested_bounding_box() {
+        let inner: HittableList = vec![sphere_at(0.0), sphere_at(4.0)].into_iter().collect();
+        let mut outer = HittableList::new();
+        outer.add(Arc::new(inner));
+        outer.add(sphere_at(-2.0));
+        let bbox = outer.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.get_lower(), Point3::new(-3.0, -1.0, -1.0));
+        assert_eq!(bbox.get_upper(), Point3::new(5.0, 1.0, 1.0));
+        assert!(HittableList::new().bounding_box(0.0, 1.0).is_none());
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::ray::Ray;
+use crate::transform::Transform;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::aabb::AABB;
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::hittable::{HitRecord, Hittable};
+
+/// Places a shared object in the scene under an affine transform. Many instances can refer to
+/// the same object, only the transform is stored per instance.
+#[derive(Clone)]
+pub struct Instance {
+    object: Arc<dyn Hittable + Sync + Send>,
+    object_to_world: Transform,
+}
+
+impl Instance {
+    #[inline(always)]
+    pub fn new(object: Arc<dyn Hittable + Sync + Send>, object_to_world: Transform) -> Instance {
+        Instance {
+            object,
+            object_to_world,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_transform(&self) -> Transform {
+        self.object_to_world
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let object_ray = self.object_to_world.inverse().transform_ray(r);
+        self.object
+            .hit(&object_ray, t_min, t_max)
+            .map(|rec| rec.transformed(&self.object_to_world))
+    }
+
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        let object_ray = self.object_to_world.inverse().transform_ray(r);
+        self.object.occluded(&object_ray, t_min, t_max)
+    }
+
+    // Only exact for rotations, translations and uniform scales, which leave solid angles as
+    // they are.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let world_to_object = self.object_to_world.inverse();
+        self.object.pdf_value(
+            world_to_object.transform_point(origin),
+            world_to_object.transform_vector(direction),
+        )
+    }
+
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        let object_origin = self.object_to_world.inverse().transform_point(origin);
+        self.object_to_world
+            .transform_vector(self.object.random_direction(object_origin, u))
+    }
+
+    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Box<dyn BoundingBox>> {
+        let bbox = self.object.bounding_box(time0, time1)?;
+        let bbox = AABB::new(bbox.get_lower(), bbox.get_upper());
+        Some(Box::new(self.object_to_world.transform_aabb(&bbox)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::cuboid::Cuboid;
+    use crate::objects::lambertian::Lambertian;
+
+    #[test]
+    fn test_rotated_translated_cuboid() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let cube = Arc::new(Cuboid::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 2.0),
+            material,
+        ));
+        let instance = Instance::new(
+            cube,
+            Transform::rotate_y(90.0).then(&Transform::translate(Vec3::new(10.0, 0.0, 0.0))),
+        );
+
+        // The cube's long side now runs along x, from x = 10 to x = 12.
+        let r = Ray::new(Point3::new(0.0, 0.5, -0.5), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let rec = instance.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((rec.get_t() - 10.0).abs() < 1e-9);
+        assert!((rec.get_point() - Point3::new(10.0, 0.5, -0.5)).length() < 1e-9);
+        assert!((rec.get_normal() - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-9);
+
+        let bbox = instance.bounding_box(0.0, 1.0).unwrap();
+        assert!((bbox.get_lower() - Point3::new(10.0, 0.0, -1.0)).length() < 1e-9);
+        assert!((bbox.get_upper() - Point3::new(12.0, 1.0, 0.0)).length() < 1e-9);
+    }
+}
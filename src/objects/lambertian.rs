@@ -1,30 +1,91 @@
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
 use crate::color::Color;
 use crate::objects::hittable::HitRecord;
-use crate::objects::material::Material;
+use crate::objects::material::{Material, ScatterRecord};
+use crate::pdfs::cosine_pdf::CosinePdf;
+use crate::pdfs::pdf::Pdf;
 use crate::ray::Ray;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::texture::Texture;
 use crate::vec3::Vec3;
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture + Sync + Send>,
 }
 
 impl Lambertian {
     #[inline(always)]
     pub fn new(albedo: Color) -> Lambertian {
+        Lambertian::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    #[inline(always)]
+    pub fn from_texture(albedo: Arc<dyn Texture + Sync + Send>) -> Lambertian {
         Lambertian { albedo }
     }
+
+    fn albedo_at(&self, record: &HitRecord) -> Color {
+        // Vertex colors tint the albedo, so a white material shows them unchanged.
+        let (u, v) = record.get_uv();
+        let albedo = self.albedo.value(u, v, record.get_point());
+        match record.get_vertex_color() {
+            Some(c) => Color::try_from(Vec3::from(albedo) * Vec3::from(c)).unwrap(),
+            None => albedo,
+        }
+    }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
-        let mut scatter_direction =
-            record.get_normal() + Vec3::random_in_unit_sphere().unit_vector();
-        if scatter_direction.near_zero() {
-            scatter_direction = record.get_normal();
-        }
-        Some((
-            self.albedo,
-            Ray::new(record.get_point(), scatter_direction, ray_in.get_time()),
-        ))
+    fn sample(&self, _ray_in: &Ray, record: &HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Diffuse {
+            pdf: Box::new(CosinePdf::new(record.get_normal())),
+        })
+    }
+
+    fn eval(&self, _ray_in: &Ray, record: &HitRecord, direction: Vec3) -> Vec3 {
+        let cosine = record.get_normal().dot(&direction.unit_vector()).max(0.0);
+        (cosine / PI) * Vec3::from(self.albedo_at(record))
+    }
+
+    fn albedo(&self, record: &HitRecord) -> Vec3 {
+        Vec3::from(self.albedo_at(record))
+    }
+
+    fn pdf(&self, _ray_in: &Ray, record: &HitRecord, direction: Vec3) -> f64 {
+        CosinePdf::new(record.get_normal()).value(direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::objects::hittable::Hittable;
+    use crate::objects::sphere::Sphere;
+    use crate::vec3::Point3;
+
+    #[test]
+    fn test_eval_over_pdf_is_albedo() {
+        let material = Arc::new(Lambertian::new(Color::new(255, 51, 0)));
+        let sphere = Sphere::new_immobile(Point3::new(0.0, 0.0, 0.0), 1.0, material.clone());
+        let r = Ray::new(Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let rec = sphere.hit(&r, 0.001, f64::INFINITY).unwrap();
+
+        let pdf = match material.sample(&r, &rec) {
+            Some(ScatterRecord::Diffuse { pdf }) => pdf,
+            _ => panic!("lambertian should scatter diffusely"),
+        };
+        let direction = pdf.generate((0.3, 0.6));
+        assert!((pdf.value(direction) - material.pdf(&r, &rec, direction)).abs() < 1e-12);
+        let weight = material.eval(&r, &rec, direction) / material.pdf(&r, &rec, direction);
+        assert!((weight - Vec3::new(1.0, 0.2, 0.0)).length() < 1e-12);
+
+        // Nothing goes below the surface.
+        let below = Vec3::new(0.2, -1.0, 0.0);
+        assert_eq!(material.eval(&r, &rec, below), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(material.pdf(&r, &rec, below), 0.0);
     }
 }
@@ -1,8 +1,44 @@
 use crate::color::Color;
+use crate::pdfs::pdf::Pdf;
 use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
 
 use crate::objects::hittable::HitRecord;
 
+/// What `Material::sample` does with an incoming ray.
+pub enum ScatterRecord {
+    /// A single outgoing ray, as from a mirror or glass. There is no density to weigh against
+    /// other strategies, so the ray is followed as is and tinted by `attenuation`.
+    Specular { attenuation: Color, ray: Ray },
+    /// Light spread over many directions. Directions may come from `pdf` or from any other
+    /// density, weighted by `Material::eval` over that density.
+    Diffuse { pdf: Box<dyn Pdf> },
+}
+
 pub trait Material {
-    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)>;
+    /// How light arriving along `ray_in` scatters, or `None` if it is absorbed.
+    fn sample(&self, ray_in: &Ray, record: &HitRecord) -> Option<ScatterRecord>;
+
+    /// The scattering function times the cosine to the normal, for light leaving along
+    /// `direction`. Zero for specular materials, which can only be followed through `sample`.
+    fn eval(&self, _ray_in: &Ray, _record: &HitRecord, _direction: Vec3) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+
+    /// The density of the pdf from `sample` at `direction`.
+    fn pdf(&self, _ray_in: &Ray, _record: &HitRecord, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// The fraction of light the surface reflects overall, for guiding denoisers and as an AOV.
+    /// Black for materials that reflect nothing.
+    fn albedo(&self, _record: &HitRecord) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Radiance given off at surface coordinates `(u, v)` and point `p`. Unlike a `Color` it is
+    /// not limited to `[0, 1]`, lights are usually much brighter than what they illuminate.
+    fn emitted(&self, _u: f64, _v: f64, _p: Point3) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
 }
@@ -1,17 +1,26 @@
+use std::sync::Arc;
+
 use crate::color::Color;
 use crate::objects::hittable::HitRecord;
-use crate::objects::material::Material;
+use crate::objects::material::{Material, ScatterRecord};
 use crate::ray::Ray;
+use crate::textures::solid_color::SolidColor;
+use crate::textures::texture::Texture;
 use crate::vec3::Vec3;
 
 pub struct Metal {
-    albedo: Color,
+    albedo: Arc<dyn Texture + Sync + Send>,
     fuzz: f64,
 }
 
 impl Metal {
     #[inline(always)]
     pub fn new(albedo: Color, fuzz: f64) -> Metal {
+        Metal::from_texture(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    #[inline(always)]
+    pub fn from_texture(albedo: Arc<dyn Texture + Sync + Send>, fuzz: f64) -> Metal {
         Metal {
             albedo,
             fuzz: if fuzz < 1.0 { fuzz } else { 1.0 },
@@ -20,7 +29,7 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray_in: &Ray, record: &HitRecord) -> Option<(Color, Ray)> {
+    fn sample(&self, ray_in: &Ray, record: &HitRecord) -> Option<ScatterRecord> {
         let reflected = ray_in
             .get_direction()
             .unit_vector()
@@ -31,9 +40,18 @@ impl Material for Metal {
             ray_in.get_time(),
         );
         if scattered.get_direction().dot(&record.get_normal()) > 0.0 {
-            Some((self.albedo, scattered))
+            let (u, v) = record.get_uv();
+            Some(ScatterRecord::Specular {
+                attenuation: self.albedo.value(u, v, record.get_point()),
+                ray: scattered,
+            })
         } else {
             None
         }
     }
+
+    fn albedo(&self, record: &HitRecord) -> Vec3 {
+        let (u, v) = record.get_uv();
+        Vec3::from(self.albedo.value(u, v, record.get_point()))
+    }
 }
@@ -1,9 +1,16 @@
 pub mod aabb;
+pub mod aarect;
 pub mod bounding_box;
 pub mod bvh;
+pub mod cuboid;
 pub mod dielectric;
+pub mod diffuse_light;
 pub mod hittable;
+pub mod hittable_list;
+pub mod instance;
 pub mod lambertian;
 pub mod material;
 pub mod metal;
 pub mod sphere;
+pub mod triangle;
+pub mod triangle_mesh;
@@ -1,6 +1,9 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::sampling::{uniform_cone, uniform_cone_pdf, uniform_sphere};
 use crate::vec3::{Point3, Vec3};
 
 use crate::objects::aabb::AABB;
@@ -48,7 +51,7 @@ impl Sphere {
             center0: center,
             center1: center,
             time0: 0.0,
-            time1: std::f64::INFINITY,
+            time1: f64::INFINITY,
             radius,
             material,
         }
@@ -68,6 +71,39 @@ impl Sphere {
     pub fn get_radius(&self) -> f64 {
         self.radius
     }
+
+    /// Spherical coordinates of a point on the unit sphere: `u` is the angle around the y axis
+    /// starting at -x, `v` runs from the bottom pole to the top.
+    pub fn get_sphere_uv(p: Point3) -> (f64, f64) {
+        let theta = (-p.get_y()).clamp(-1.0, 1.0).acos();
+        let phi = (-p.get_z()).atan2(p.get_x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    // The cosine of the half-angle the sphere covers seen from `origin`, `None` from inside.
+    fn cos_max_from(&self, origin: Point3) -> Option<f64> {
+        let distance_squared = (self.get_center(self.time0) - origin).length_squared();
+        let radius_squared = self.radius.powi(2);
+        if distance_squared <= radius_squared {
+            None
+        } else {
+            Some((1.0 - (radius_squared / distance_squared)).sqrt())
+        }
+    }
+
+    // Partial derivatives of the `get_sphere_uv` parameterization at a point on the unit sphere.
+    fn get_derivatives(&self, p: Point3) -> (Vec3, Vec3) {
+        let dpdu = (2.0 * PI * self.radius) * Vec3::new(p.get_z(), 0.0, -p.get_x());
+        // sin(theta) is zero at the poles, where x and z are too and dpdv vanishes.
+        let sin_theta = (1.0 - p.get_y().powi(2)).max(0.0).sqrt().max(1e-12);
+        let dpdv = (PI * self.radius / sin_theta)
+            * Vec3::new(
+                -p.get_x() * p.get_y(),
+                sin_theta.powi(2),
+                -p.get_z() * p.get_y(),
+            );
+        (dpdu, dpdv)
+    }
 }
 
 impl Hittable for Sphere {
@@ -93,16 +129,37 @@ impl Hittable for Sphere {
 
         let p = r.at(root);
         let outward_normal = (p - self.get_center(r.get_time())) / self.radius;
-        Some(HitRecord::from_outward_normal(
-            p,
-            root,
-            r,
-            outward_normal,
-            self.material.clone(),
-        ))
+        let (dpdu, dpdv) = self.get_derivatives(outward_normal);
+        Some(
+            HitRecord::from_outward_normal(p, root, r, outward_normal, self.material.clone())
+                .with_uv(Sphere::get_sphere_uv(outward_normal))
+                .with_derivatives(dpdu, dpdv),
+        )
+    }
+
+    // Sampled over the cone of directions it covers, or the whole sphere from inside. Moving
+    // spheres are sampled where they are at the start of their motion.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let r = Ray::new(origin, direction, self.time0);
+        if self.hit(&r, 0.001, f64::INFINITY).is_none() {
+            return 0.0;
+        }
+        match self.cos_max_from(origin) {
+            Some(cos_max) => uniform_cone_pdf(cos_max),
+            None => 1.0 / (4.0 * PI),
+        }
     }
 
-    fn bounding_box(&self, time0: f64, time1: f64) -> Option<Box<dyn BoundingBox>> {
+    fn random_direction(&self, origin: Point3, u: (f64, f64)) -> Vec3 {
+        match self.cos_max_from(origin) {
+            Some(cos_max) => {
+                Onb::from_w(self.get_center(self.time0) - origin).local(uniform_cone(u, cos_max))
+            }
+            None => uniform_sphere(u),
+        }
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
         let radius_vec = Vec3::new(self.radius, self.radius, self.radius);
         if self.center0 == self.center1 {
             Some(Box::new(AABB::new(
@@ -122,3 +179,46 @@ impl Hittable for Sphere {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+
+    #[test]
+    fn test_sphere_uv() {
+        let uv = |x, y, z| Sphere::get_sphere_uv(Point3::new(x, y, z));
+        assert_eq!(uv(-1.0, 0.0, 0.0), (0.0, 0.5));
+        assert_eq!(uv(1.0, 0.0, 0.0), (0.5, 0.5));
+        assert_eq!(uv(0.0, 0.0, 1.0), (0.25, 0.5));
+        assert_eq!(uv(0.0, 1.0, 0.0).1, 1.0);
+        assert_eq!(uv(0.0, -1.0, 0.0).1, 0.0);
+    }
+
+    #[test]
+    fn test_derivatives_match_finite_differences() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let sphere = Sphere::new_immobile(Point3::new(1.0, 2.0, 3.0), 2.0, material);
+        let r = Ray::new(
+            Point3::new(-5.0, 3.0, 4.0),
+            Point3::new(1.0, 2.0, 3.0) - Point3::new(-5.0, 3.0, 4.0),
+            0.0,
+        );
+        let rec = sphere.hit(&r, 0.001, f64::INFINITY).unwrap();
+        let (u, v) = rec.get_uv();
+        let n = (rec.get_point() - Point3::new(1.0, 2.0, 3.0)) / 2.0;
+
+        // Moving the hit point along dpdu and dpdv should move the uv by the same small step.
+        let h = 1e-6;
+        let (u1, v1) = Sphere::get_sphere_uv(n + ((h / 2.0) * rec.get_dpdu()));
+        let (u2, v2) = Sphere::get_sphere_uv(n + ((h / 2.0) * rec.get_dpdv()));
+        assert!(((u1 - u) / h - 1.0).abs() < 1e-4 && (v1 - v).abs() < 1e-9);
+        assert!(((v2 - v) / h - 1.0).abs() < 1e-4 && (u2 - u).abs() < 1e-9);
+
+        let frame = rec.get_tangent_frame();
+        assert!((frame.get_w() - rec.get_normal()).length() < 1e-12);
+        assert!((frame.get_u() - rec.get_dpdu().unit_vector()).length() < 1e-12);
+    }
+}
@@ -0,0 +1,322 @@
+use std::convert::TryFrom;
+use std::mem::swap;
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::aabb::AABB;
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::hittable::{HitRecord, Hittable};
+use crate::objects::material::Material;
+
+#[derive(Clone)]
+pub struct Triangle {
+    vertices: [Point3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: [(f64, f64); 3],
+    colors: Option<[Color; 3]>,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl Triangle {
+    #[inline(always)]
+    pub fn new(
+        v0: Point3,
+        v1: Point3,
+        v2: Point3,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Triangle {
+        Triangle {
+            vertices: [v0, v1, v2],
+            normals: None,
+            uvs: [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+            colors: None,
+            material,
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Triangle {
+        self.normals = Some(normals);
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Triangle {
+        self.uvs = uvs;
+        self
+    }
+
+    #[inline(always)]
+    pub fn with_colors(mut self, colors: [Color; 3]) -> Triangle {
+        self.colors = Some(colors);
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_vertices(&self) -> [Point3; 3] {
+        self.vertices
+    }
+
+    #[inline(always)]
+    pub fn get_geometric_normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        (v1 - v0).cross(&(v2 - v0)).unit_vector()
+    }
+
+    pub fn get_uv(&self, barycentrics: Vec3) -> (f64, f64) {
+        let [uv0, uv1, uv2] = self.uvs;
+        (
+            (barycentrics.get_x() * uv0.0)
+                + (barycentrics.get_y() * uv1.0)
+                + (barycentrics.get_z() * uv2.0),
+            (barycentrics.get_x() * uv0.1)
+                + (barycentrics.get_y() * uv1.1)
+                + (barycentrics.get_z() * uv2.1),
+        )
+    }
+
+    /// Watertight ray/triangle intersection (Woop, Benthin and Wald 2013), returning the ray
+    /// distance and barycentric coordinates of the hit.
+    ///
+    /// The vertices are sheared into a space where the ray runs along +z from the origin, so the
+    /// edge tests of two triangles sharing an edge are evaluated with exactly the same arithmetic
+    /// and a ray can never slip through the crack between them.
+    pub fn intersect(
+        vertices: &[Point3; 3],
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(f64, Vec3)> {
+        let dir = r.get_direction();
+        let origin = r.get_origin();
+
+        let kz = if dir.get_x().abs() > dir.get_y().abs() && dir.get_x().abs() > dir.get_z().abs() {
+            0
+        } else if dir.get_y().abs() > dir.get_z().abs() {
+            1
+        } else {
+            2
+        };
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+        if dir[kz] < 0.0 {
+            swap(&mut kx, &mut ky);
+        }
+
+        let sx = dir[kx] / dir[kz];
+        let sy = dir[ky] / dir[kz];
+        let sz = 1.0 / dir[kz];
+
+        let a = vertices[0] - origin;
+        let b = vertices[1] - origin;
+        let c = vertices[2] - origin;
+
+        let ax = a[kx] - (sx * a[kz]);
+        let ay = a[ky] - (sy * a[kz]);
+        let bx = b[kx] - (sx * b[kz]);
+        let by = b[ky] - (sy * b[kz]);
+        let cx = c[kx] - (sx * c[kz]);
+        let cy = c[ky] - (sy * c[kz]);
+
+        let u = (cx * by) - (cy * bx);
+        let v = (ax * cy) - (ay * cx);
+        let w = (bx * ay) - (by * ax);
+
+        if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+            return None;
+        }
+        let det = u + v + w;
+        if det == 0.0 {
+            return None;
+        }
+
+        let az = sz * a[kz];
+        let bz = sz * b[kz];
+        let cz = sz * c[kz];
+        let t = ((u * az) + (v * bz) + (w * cz)) / det;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        Some((t, Vec3::new(u / det, v / det, w / det)))
+    }
+
+    /// How the surface moves with the texture coordinates, zero when the uvs are degenerate.
+    pub fn get_derivatives(&self) -> (Vec3, Vec3) {
+        let [uv0, uv1, uv2] = self.uvs;
+        let (du02, dv02) = (uv0.0 - uv2.0, uv0.1 - uv2.1);
+        let (du12, dv12) = (uv1.0 - uv2.0, uv1.1 - uv2.1);
+        let dp02 = self.vertices[0] - self.vertices[2];
+        let dp12 = self.vertices[1] - self.vertices[2];
+        let det = (du02 * dv12) - (dv02 * du12);
+        if det.abs() < 1e-12 {
+            return (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0));
+        }
+        (
+            ((dv12 * dp02) - (dv02 * dp12)) / det,
+            ((du02 * dp12) - (du12 * dp02)) / det,
+        )
+    }
+
+    /// Builds the full record for a hit found by `intersect`, interpolating the per-vertex
+    /// attributes.
+    pub fn hit_record(&self, r: &Ray, t: f64, barycentrics: Vec3) -> HitRecord {
+        let point = (barycentrics.get_x() * self.vertices[0])
+            + (barycentrics.get_y() * self.vertices[1])
+            + (barycentrics.get_z() * self.vertices[2]);
+        let mut geometric_normal = self.get_geometric_normal();
+        let shading_normal = match self.normals {
+            Some([n0, n1, n2]) => {
+                let n = ((barycentrics.get_x() * n0)
+                    + (barycentrics.get_y() * n1)
+                    + (barycentrics.get_z() * n2))
+                    .unit_vector();
+                // Trust the supplied normals over the winding order for which side is outside.
+                if geometric_normal.dot(&n) < 0.0 {
+                    geometric_normal = -geometric_normal;
+                }
+                n
+            }
+            None => geometric_normal,
+        };
+
+        let (dpdu, dpdv) = self.get_derivatives();
+        let rec =
+            HitRecord::from_outward_normal(point, t, r, geometric_normal, self.material.clone())
+                .with_shading_normal(shading_normal)
+                .with_barycentrics(barycentrics)
+                .with_uv(self.get_uv(barycentrics))
+                .with_derivatives(dpdu, dpdv);
+        match self.colors {
+            Some([c0, c1, c2]) => rec.with_vertex_color(
+                Color::try_from(
+                    (barycentrics.get_x() * Vec3::from(c0))
+                        + (barycentrics.get_y() * Vec3::from(c1))
+                        + (barycentrics.get_z() * Vec3::from(c2)),
+                )
+                .unwrap_or(c0),
+            ),
+            None => rec,
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, barycentrics) = Triangle::intersect(&self.vertices, r, t_min, t_max)?;
+        Some(self.hit_record(r, t, barycentrics))
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        let [v0, v1, v2] = self.vertices;
+        Some(Box::new(AABB::surrounding_box(
+            AABB::new(v0, v0),
+            AABB::surrounding_box(AABB::new(v1, v1), AABB::new(v2, v2)),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+
+    fn material() -> Arc<dyn Material + Sync + Send> {
+        Arc::new(Lambertian::new(Color::new(127, 127, 127)))
+    }
+
+    #[test]
+    fn test_hit_barycentrics() {
+        let tri = Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            material(),
+        );
+        let r = Ray::new(Point3::new(0.25, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let rec = tri.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_eq!(rec.get_t(), 2.0);
+        assert_eq!(rec.get_barycentrics(), Vec3::new(0.25, 0.25, 0.5));
+        assert_eq!(rec.get_geometric_normal(), Vec3::new(0.0, 0.0, 1.0));
+        assert!(rec.is_front_face());
+
+        let r = Ray::new(Point3::new(0.75, 0.5, 2.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(tri.hit(&r, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn test_shading_normal_follows_geometric_side() {
+        let n = Vec3::new(0.0, 0.0, 1.0);
+        let tri = Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            material(),
+        )
+        .with_normals([n, n, n]);
+        let r = Ray::new(Point3::new(0.25, 0.25, -2.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let rec = tri.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!(!rec.is_front_face());
+        assert_eq!(rec.get_normal(), -n);
+        assert_eq!(rec.get_geometric_normal(), -n);
+    }
+
+    #[test]
+    fn test_shared_edge_is_watertight() {
+        let v = [
+            Point3::new(-1.0, -1.0, 0.0),
+            Point3::new(1.0, -1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(-1.0, 1.0, 0.0),
+        ];
+        let first = Triangle::new(v[0], v[1], v[2], material());
+        let second = Triangle::new(v[0], v[2], v[3], material());
+        let origin = Point3::new(0.1, 0.3, 5.0);
+        for i in 0..=100 {
+            let s = -1.0 + (i as f64 / 50.0);
+            let r = Ray::new(origin, Point3::new(s, s, 0.0) - origin, 0.0);
+            assert!(
+                first.hit(&r, 0.0, f64::INFINITY).is_some()
+                    || second.hit(&r, 0.0, f64::INFINITY).is_some(),
+                "ray towards ({}, {}) fell through the shared edge",
+                s,
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_derivatives_from_uvs() {
+        let tri = Triangle::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(0.0, 3.0, 0.0),
+            material(),
+        )
+        .with_uvs([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        let (dpdu, dpdv) = tri.get_derivatives();
+        assert_eq!(dpdu, Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(dpdv, Vec3::new(0.0, 3.0, 0.0));
+
+        let flat = tri.with_uvs([(0.5, 0.5); 3]);
+        assert_eq!(flat.get_derivatives().0, Vec3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_bounding_box_is_tight() {
+        let tri = Triangle::new(
+            Point3::new(0.0, 2.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, -3.0),
+            material(),
+        );
+        let bbox = tri.bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.get_lower(), Point3::new(0.0, 0.0, -3.0));
+        assert_eq!(bbox.get_upper(), Point3::new(1.0, 2.0, 0.0));
+    }
+}
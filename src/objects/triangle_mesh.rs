@@ -0,0 +1,226 @@
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+use crate::ray::Ray;
+
+use crate::objects::aabb::AABB;
+use crate::objects::bounding_box::BoundingBox;
+use crate::objects::bvh::{LinearBvh, SplitMethod};
+use crate::objects::hittable::{HitRecord, Hittable};
+use crate::objects::material::Material;
+use crate::objects::triangle::Triangle;
+
+/// Indexed triangle storage with a single material. `normals`, `uvs` and `colors` are either
+/// empty or hold one entry per position, every triangle refers to its three corners by position
+/// index. The mesh keeps its own hierarchy over the triangles so it is one `Hittable` in a
+/// scene, an individual `Triangle` is only built for the closest hit.
+pub struct TriangleMesh {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    colors: Vec<Color>,
+    indices: Vec<[usize; 3]>,
+    material: Arc<dyn Material + Sync + Send>,
+    bvh: LinearBvh,
+}
+
+impl TriangleMesh {
+    pub fn new(
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        indices: Vec<[usize; 3]>,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> TriangleMesh {
+        assert!(
+            normals.is_empty() || normals.len() == positions.len(),
+            "TriangleMesh needs no normals or one per position"
+        );
+        assert!(
+            uvs.is_empty() || uvs.len() == positions.len(),
+            "TriangleMesh needs no uvs or one per position"
+        );
+        assert!(
+            indices.iter().flatten().all(|&i| i < positions.len()),
+            "TriangleMesh index out of range"
+        );
+        let bounds: Vec<AABB> = indices
+            .iter()
+            .map(|&[i0, i1, i2]| {
+                AABB::surrounding_box(
+                    AABB::from_point(positions[i0]),
+                    AABB::surrounding_box(
+                        AABB::from_point(positions[i1]),
+                        AABB::from_point(positions[i2]),
+                    ),
+                )
+            })
+            .collect();
+        let bvh = LinearBvh::new(&bounds, SplitMethod::default(), 4);
+        TriangleMesh {
+            positions,
+            normals,
+            uvs,
+            colors: Vec::new(),
+            indices,
+            material,
+            bvh,
+        }
+    }
+
+    pub fn with_colors(mut self, colors: Vec<Color>) -> TriangleMesh {
+        assert!(
+            colors.is_empty() || colors.len() == self.positions.len(),
+            "TriangleMesh needs no colors or one per position"
+        );
+        self.colors = colors;
+        self
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn get_positions(&self) -> &[Point3] {
+        &self.positions
+    }
+
+    #[inline(always)]
+    pub fn get_normals(&self) -> &[Vec3] {
+        &self.normals
+    }
+
+    #[inline(always)]
+    pub fn get_uvs(&self) -> &[(f64, f64)] {
+        &self.uvs
+    }
+
+    #[inline(always)]
+    pub fn get_colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    #[inline(always)]
+    pub fn get_indices(&self) -> &[[usize; 3]] {
+        &self.indices
+    }
+
+    #[inline(always)]
+    pub fn get_material(&self) -> Arc<dyn Material + Sync + Send> {
+        self.material.clone()
+    }
+
+    pub fn triangle(&self, index: usize) -> Triangle {
+        let [i0, i1, i2] = self.indices[index];
+        let mut tri = Triangle::new(
+            self.positions[i0],
+            self.positions[i1],
+            self.positions[i2],
+            self.material.clone(),
+        );
+        if !self.normals.is_empty() {
+            tri = tri.with_normals([self.normals[i0], self.normals[i1], self.normals[i2]]);
+        }
+        if !self.uvs.is_empty() {
+            tri = tri.with_uvs([self.uvs[i0], self.uvs[i1], self.uvs[i2]]);
+        }
+        if !self.colors.is_empty() {
+            tri = tri.with_colors([self.colors[i0], self.colors[i1], self.colors[i2]]);
+        }
+        tri
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        (0..self.len()).map(move |i| self.triangle(i))
+    }
+
+    #[inline(always)]
+    fn corners(&self, index: usize) -> [Point3; 3] {
+        let [i0, i1, i2] = self.indices[index];
+        [self.positions[i0], self.positions[i1], self.positions[i2]]
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, (barycentrics, index)) = self.bvh.closest(r, t_min, t_max, |index, closest| {
+            let (t, barycentrics) = Triangle::intersect(&self.corners(index), r, t_min, closest)?;
+            Some((t, (barycentrics, index)))
+        })?;
+        Some(self.triangle(index).hit_record(r, t, barycentrics))
+    }
+
+    fn occluded(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.bvh.occluded(r, t_min, t_max, |index| {
+            Triangle::intersect(&self.corners(index), r, t_min, t_max).is_some()
+        })
+    }
+
+    fn bounding_box(&self, _time0: f64, _time1: f64) -> Option<Box<dyn BoundingBox>> {
+        self.bvh
+            .get_bounds()
+            .map(|b| Box::new(b) as Box<dyn BoundingBox>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::objects::lambertian::Lambertian;
+
+    // A 10x10 grid of unit quads in the z = 0 plane.
+    fn grid() -> TriangleMesh {
+        let mut positions = Vec::new();
+        for y in 0..11 {
+            for x in 0..11 {
+                positions.push(Point3::new(x as f64, y as f64, 0.0));
+            }
+        }
+        let mut indices = Vec::new();
+        for y in 0..10 {
+            for x in 0..10 {
+                let i = (y * 11) + x;
+                indices.push([i, i + 1, i + 12]);
+                indices.push([i, i + 12, i + 11]);
+            }
+        }
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        TriangleMesh::new(positions, Vec::new(), Vec::new(), indices, material)
+    }
+
+    #[test]
+    fn test_hit_matches_triangles() {
+        let mesh = grid();
+        assert_eq!(mesh.len(), 200);
+        for i in 0..50 {
+            let target = Point3::new(0.1 + (i as f64 * 0.2), 9.9 - (i as f64 * 0.19), 0.0);
+            let origin = Point3::new(5.0, 5.0, 3.0);
+            let r = Ray::new(origin, target - origin, 0.0);
+            let expected = mesh
+                .triangles()
+                .find_map(|t| t.hit(&r, 0.001, f64::INFINITY))
+                .unwrap();
+            let actual = mesh.hit(&r, 0.001, f64::INFINITY).unwrap();
+            assert_eq!(actual.get_t(), expected.get_t());
+            assert_eq!(actual.get_point(), expected.get_point());
+        }
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = grid().bounding_box(0.0, 1.0).unwrap();
+        assert_eq!(bbox.get_lower(), Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(bbox.get_upper(), Point3::new(10.0, 10.0, 0.0));
+    }
+}
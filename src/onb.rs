@@ -0,0 +1,111 @@
+use crate::vec3::Vec3;
+
+/// An orthonormal basis with `w` as the surface normal and `u`, `v` spanning the tangent plane.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    /// A basis around `w` with an arbitrary but continuous choice of tangent (Duff et al. 2017).
+    pub fn from_w(w: Vec3) -> Onb {
+        let w = w.unit_vector();
+        let sign = 1.0_f64.copysign(w.get_z());
+        let a = -1.0 / (sign + w.get_z());
+        let b = w.get_x() * w.get_y() * a;
+        let u = Vec3::new(
+            1.0 + (sign * w.get_x() * w.get_x() * a),
+            sign * b,
+            -sign * w.get_x(),
+        );
+        let v = Vec3::new(b, sign + (w.get_y() * w.get_y() * a), -w.get_y());
+        Onb { u, v, w }
+    }
+
+    /// A basis around `w` whose `u` is `tangent` made perpendicular to `w`. Falls back to
+    /// `from_w` when the tangent is missing or parallel to `w`.
+    pub fn from_w_and_tangent(w: Vec3, tangent: Vec3) -> Onb {
+        let w = w.unit_vector();
+        let u = tangent - (tangent.dot(&w) * w);
+        if u.length_squared() < 1e-16 {
+            return Onb::from_w(w);
+        }
+        let u = u.unit_vector();
+        Onb {
+            u,
+            v: w.cross(&u),
+            w,
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_u(&self) -> Vec3 {
+        self.u
+    }
+
+    #[inline(always)]
+    pub fn get_v(&self) -> Vec3 {
+        self.v
+    }
+
+    #[inline(always)]
+    pub fn get_w(&self) -> Vec3 {
+        self.w
+    }
+
+    /// Converts coordinates in this basis to a world space vector.
+    #[inline(always)]
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        (a.get_x() * self.u) + (a.get_y() * self.v) + (a.get_z() * self.w)
+    }
+
+    /// Converts a world space vector to coordinates in this basis.
+    #[inline(always)]
+    pub fn to_local(&self, a: Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_orthonormal(onb: &Onb) {
+        for (a, b) in &[
+            (onb.get_u(), onb.get_v()),
+            (onb.get_v(), onb.get_w()),
+            (onb.get_w(), onb.get_u()),
+        ] {
+            assert!(a.dot(b).abs() < 1e-12);
+            assert!((a.length() - 1.0).abs() < 1e-12);
+        }
+        // Right handed.
+        assert!((onb.get_u().cross(&onb.get_v()) - onb.get_w()).length() < 1e-12);
+    }
+
+    #[test]
+    fn test_from_w() {
+        for w in &[
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, 2.0, -3.0),
+        ] {
+            let onb = Onb::from_w(*w);
+            assert_orthonormal(&onb);
+            let a = Vec3::new(0.3, -0.2, 0.9);
+            assert!((onb.to_local(onb.local(a)) - a).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_from_w_and_tangent() {
+        let onb = Onb::from_w_and_tangent(Vec3::new(0.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        assert_orthonormal(&onb);
+        assert!((onb.get_u() - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-12);
+
+        let parallel = Onb::from_w_and_tangent(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 2.0, 0.0));
+        assert_orthonormal(&parallel);
+    }
+}
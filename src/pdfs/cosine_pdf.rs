@@ -0,0 +1,53 @@
+use std::f64::consts::PI;
+
+use crate::onb::Onb;
+use crate::sampling::cosine_hemisphere;
+use crate::vec3::Vec3;
+
+use crate::pdfs::pdf::Pdf;
+
+/// Directions around a normal weighted by the cosine to it, matching a diffuse surface.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    #[inline(always)]
+    pub fn new(normal: Vec3) -> CosinePdf {
+        CosinePdf {
+            uvw: Onb::from_w(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cosine = direction.unit_vector().dot(&self.uvw.get_w());
+        cosine.max(0.0) / PI
+    }
+
+    #[inline(always)]
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        self.uvw.local(cosine_hemisphere(u))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_match_density() {
+        let pdf = CosinePdf::new(Vec3::new(1.0, 2.0, -0.5));
+        let normal = Vec3::new(1.0, 2.0, -0.5).unit_vector();
+        for i in 0..8 {
+            for j in 0..8 {
+                let d = pdf.generate(((i as f64 + 0.5) / 8.0, j as f64 / 8.0));
+                assert!(d.dot(&normal) > 0.0);
+                assert!((pdf.value(d) - (d.dot(&normal) / PI)).abs() < 1e-12);
+            }
+        }
+        assert_eq!(pdf.value(-normal), 0.0);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::vec3::{Point3, Vec3};
+
+use crate::objects::hittable::Hittable;
+use crate::pdfs::pdf::Pdf;
+
+/// Directions from `origin` towards an object, usually a light, using the object's own
+/// `pdf_value` and `random_direction`.
+#[derive(Clone, Copy)]
+pub struct HittablePdf<'a> {
+    object: &'a dyn Hittable,
+    origin: Point3,
+}
+
+impl<'a> HittablePdf<'a> {
+    #[inline(always)]
+    pub fn new(object: &'a dyn Hittable, origin: Point3) -> HittablePdf<'a> {
+        HittablePdf { object, origin }
+    }
+}
+
+impl<'a> Pdf for HittablePdf<'a> {
+    #[inline(always)]
+    fn value(&self, direction: Vec3) -> f64 {
+        self.object.pdf_value(self.origin, direction)
+    }
+
+    #[inline(always)]
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        self.object.random_direction(self.origin, u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::color::Color;
+    use crate::objects::lambertian::Lambertian;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+
+    #[test]
+    fn test_directions_reach_object() {
+        let material = Arc::new(Lambertian::new(Color::new(127, 127, 127)));
+        let sphere = Sphere::new_immobile(Point3::new(0.0, 5.0, 0.0), 1.0, material);
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        let pdf = HittablePdf::new(&sphere, origin);
+        // The sphere covers a cone with cos(theta) = sqrt(24) / 5.
+        let expected = 1.0 / (2.0 * std::f64::consts::PI * (1.0 - (24.0_f64.sqrt() / 5.0)));
+        for i in 0..8 {
+            for j in 0..8 {
+                let d = pdf.generate(((i as f64 + 0.5) / 8.0, (j as f64 + 0.5) / 8.0));
+                assert!(sphere
+                    .hit(&Ray::new(origin, d, 0.0), 0.001, f64::INFINITY)
+                    .is_some());
+                assert!((pdf.value(d) - expected).abs() < 1e-9);
+            }
+        }
+        assert_eq!(pdf.value(Vec3::new(1.0, 0.0, 0.0)), 0.0);
+    }
+}
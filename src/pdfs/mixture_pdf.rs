@@ -0,0 +1,71 @@
+use crate::vec3::Vec3;
+
+use crate::pdfs::pdf::Pdf;
+
+/// Picks between two densities, the first with probability `weight`. Mixing light sampling
+/// with a material's own sampling keeps either from being left with no way to find a path.
+#[derive(Clone, Copy)]
+pub struct MixturePdf<'a> {
+    first: &'a dyn Pdf,
+    second: &'a dyn Pdf,
+    weight: f64,
+}
+
+impl<'a> MixturePdf<'a> {
+    /// An even mix of both.
+    #[inline(always)]
+    pub fn new(first: &'a dyn Pdf, second: &'a dyn Pdf) -> MixturePdf<'a> {
+        MixturePdf::with_weight(first, second, 0.5)
+    }
+
+    #[inline(always)]
+    pub fn with_weight(first: &'a dyn Pdf, second: &'a dyn Pdf, weight: f64) -> MixturePdf<'a> {
+        MixturePdf {
+            first,
+            second,
+            weight: weight.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl<'a> Pdf for MixturePdf<'a> {
+    fn value(&self, direction: Vec3) -> f64 {
+        (self.weight * self.first.value(direction))
+            + ((1.0 - self.weight) * self.second.value(direction))
+    }
+
+    // The first number both chooses a density and, stretched back to [0, 1), samples it.
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        if u.0 < self.weight {
+            self.first.generate((u.0 / self.weight, u.1))
+        } else {
+            let remapped = (u.0 - self.weight) / (1.0 - self.weight);
+            self.second.generate((remapped, u.1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f64::consts::PI;
+
+    use crate::pdfs::cosine_pdf::CosinePdf;
+    use crate::pdfs::uniform_sphere_pdf::UniformSpherePdf;
+
+    #[test]
+    fn test_weighted_mix() {
+        let cosine = CosinePdf::new(Vec3::new(0.0, 0.0, 1.0));
+        let sphere = UniformSpherePdf::new();
+        let mix = MixturePdf::with_weight(&cosine, &sphere, 0.25);
+        let up = Vec3::new(0.0, 0.0, 1.0);
+        let down = Vec3::new(0.0, 0.0, -1.0);
+        assert!((mix.value(up) - ((0.25 / PI) + (0.75 / (4.0 * PI)))).abs() < 1e-12);
+        assert!((mix.value(down) - (0.75 / (4.0 * PI))).abs() < 1e-12);
+
+        // Below the weight the cosine density is sampled, which never points down.
+        assert_eq!(mix.generate((0.1, 0.3)), cosine.generate((0.4, 0.3)));
+        assert_eq!(mix.generate((0.625, 0.3)), sphere.generate((0.5, 0.3)));
+    }
+}
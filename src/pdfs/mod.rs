@@ -0,0 +1,5 @@
+pub mod cosine_pdf;
+pub mod hittable_pdf;
+pub mod mixture_pdf;
+pub mod pdf;
+pub mod uniform_sphere_pdf;
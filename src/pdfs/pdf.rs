@@ -0,0 +1,11 @@
+use crate::vec3::Vec3;
+
+/// A probability density over directions, measured per unit solid angle.
+pub trait Pdf {
+    /// The density of `generate` picking `direction`, which need not be of unit length.
+    fn value(&self, direction: Vec3) -> f64;
+
+    /// Maps two uniform numbers in `[0, 1)` to a direction distributed like `value`, not
+    /// necessarily of unit length.
+    fn generate(&self, u: (f64, f64)) -> Vec3;
+}
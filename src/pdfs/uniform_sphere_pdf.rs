@@ -0,0 +1,29 @@
+use std::f64::consts::PI;
+
+use crate::sampling::uniform_sphere;
+use crate::vec3::Vec3;
+
+use crate::pdfs::pdf::Pdf;
+
+/// Every direction equally likely.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UniformSpherePdf;
+
+impl UniformSpherePdf {
+    #[inline(always)]
+    pub fn new() -> UniformSpherePdf {
+        UniformSpherePdf
+    }
+}
+
+impl Pdf for UniformSpherePdf {
+    #[inline(always)]
+    fn value(&self, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    #[inline(always)]
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        uniform_sphere(u)
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{Result, Write};
+
+use super::color::LinearRgb;
+
+/// Writes a color Portable Float Map. `pixels` are in row order from the top down, the file
+/// stores them from the bottom up.
+pub fn write_pfm<W: Write>(
+    mut writer: W,
+    width: usize,
+    height: usize,
+    pixels: &[LinearRgb],
+) -> Result<()> {
+    assert_eq!(
+        pixels.len(),
+        width * height,
+        "pixel count does not match size"
+    );
+    // A negative scale marks the data as little endian.
+    write!(writer, "PF\n{} {}\n-1.0\n", width, height)?;
+    for row in pixels.chunks(width).rev() {
+        for p in row {
+            writer.write_all(&p.get_red().to_le_bytes())?;
+            writer.write_all(&p.get_green().to_le_bytes())?;
+            writer.write_all(&p.get_blue().to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bottom_row_first() {
+        let pixels = [
+            LinearRgb::new(1.0, 2.0, 3.0),
+            LinearRgb::new(40.0, 0.5, 0.0),
+        ];
+        let mut output = Vec::new();
+        write_pfm(&mut output, 1, 2, &pixels).unwrap();
+        let header = b"PF\n1 2\n-1.0\n";
+        assert_eq!(output[..header.len()], header[..]);
+        assert_eq!(output.len(), header.len() + 24);
+        assert_eq!(
+            output[header.len()..(header.len() + 4)],
+            40.0_f32.to_le_bytes()
+        );
+        assert_eq!(output[(output.len() - 4)..], 3.0_f32.to_le_bytes());
+    }
+}
@@ -0,0 +1,240 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+/// A piecewise-constant function over `[0, 1]` that can be sampled in proportion to its value,
+/// built from `n` equally wide steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution1D {
+    func: Vec<f64>,
+    cdf: Vec<f64>,
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Negative values are treated as zero. A function that is zero everywhere is sampled
+    /// uniformly instead.
+    pub fn new(func: Vec<f64>) -> Distribution1D {
+        assert!(!func.is_empty(), "distribution needs at least one value");
+        let func: Vec<f64> = func.into_iter().map(|f| f.max(0.0)).collect();
+        let n = func.len() as f64;
+
+        let mut cdf = Vec::with_capacity(func.len() + 1);
+        cdf.push(0.0);
+        for (i, f) in func.iter().enumerate() {
+            cdf.push(cdf[i] + (f / n));
+        }
+        let integral = cdf[func.len()];
+        if integral > 0.0 {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        } else {
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f64 / n);
+        }
+        Distribution1D {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.func.is_empty()
+    }
+
+    /// The integral of the function over `[0, 1]`.
+    #[inline(always)]
+    pub fn get_integral(&self) -> f64 {
+        self.integral
+    }
+
+    // The step whose cdf range contains `u`.
+    fn find_step(&self, u: f64) -> usize {
+        let upper = self.cdf.partition_point(|&c| c <= u);
+        upper.saturating_sub(1).min(self.func.len() - 1)
+    }
+
+    /// Maps a uniform `u` in `[0, 1)` to `x` in `[0, 1)` distributed like the function, returning
+    /// `x`, its density and the step it fell in.
+    pub fn sample_continuous(&self, u: f64) -> (f64, f64, usize) {
+        let offset = self.find_step(u);
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0.0 {
+            (u - self.cdf[offset]) / width
+        } else {
+            0.0
+        };
+        let x = (offset as f64 + du) / self.func.len() as f64;
+        (x, self.pdf_step(offset), offset)
+    }
+
+    /// Picks step `i` with probability proportional to its value, returning it and that
+    /// probability.
+    pub fn sample_discrete(&self, u: f64) -> (usize, f64) {
+        let offset = self.find_step(u);
+        (offset, self.cdf[offset + 1] - self.cdf[offset])
+    }
+
+    /// The density of `sample_continuous` at `x`.
+    #[inline(always)]
+    pub fn pdf(&self, x: f64) -> f64 {
+        let offset = ((x * self.func.len() as f64) as usize).min(self.func.len() - 1);
+        self.pdf_step(offset)
+    }
+
+    #[inline(always)]
+    fn pdf_step(&self, offset: usize) -> f64 {
+        if self.integral > 0.0 {
+            self.func[offset] / self.integral
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A piecewise-constant function over `[0, 1]^2`, sampled by picking a row from the marginal
+/// distribution and then a column from that row's conditional distribution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution2D {
+    conditional: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// `func` is `width * height` values in row-major order. The first coordinate of samples
+    /// runs along a row, the second across rows.
+    pub fn new(func: &[f64], width: usize, height: usize) -> Distribution2D {
+        assert_eq!(
+            func.len(),
+            width * height,
+            "value count does not match size"
+        );
+        let conditional: Vec<Distribution1D> = func
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|c| c.get_integral()).collect());
+        Distribution2D {
+            conditional,
+            marginal,
+        }
+    }
+
+    /// Maps two uniform numbers to a point distributed like the function and its density.
+    pub fn sample_continuous(&self, u: (f64, f64)) -> ((f64, f64), f64) {
+        let (y, pdf_y, row) = self.marginal.sample_continuous(u.1);
+        let (x, pdf_x, _) = self.conditional[row].sample_continuous(u.0);
+        ((x, y), pdf_x * pdf_y)
+    }
+
+    /// The density of `sample_continuous` at `p`.
+    pub fn pdf(&self, p: (f64, f64)) -> f64 {
+        let rows = self.conditional.len();
+        let row = ((p.1 * rows as f64) as usize).min(rows - 1);
+        self.marginal.pdf(p.1) * self.conditional[row].pdf(p.0)
+    }
+}
+
+/// A direction uniformly distributed over the unit sphere, with density `1 / 4pi`.
+pub fn uniform_sphere(u: (f64, f64)) -> Vec3 {
+    let z = 1.0 - (2.0 * u.0);
+    let r = (1.0 - (z * z)).max(0.0).sqrt();
+    let phi = 2.0 * PI * u.1;
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// A direction uniformly distributed inside the cone around +z whose half-angle has cosine
+/// `cos_max`, with density `uniform_cone_pdf(cos_max)`.
+pub fn uniform_cone(u: (f64, f64), cos_max: f64) -> Vec3 {
+    let cos_theta = (1.0 - u.0) + (u.0 * cos_max);
+    let sin_theta = (1.0 - (cos_theta * cos_theta)).max(0.0).sqrt();
+    let phi = 2.0 * PI * u.1;
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+#[inline(always)]
+pub fn uniform_cone_pdf(cos_max: f64) -> f64 {
+    1.0 / (2.0 * PI * (1.0 - cos_max))
+}
+
+/// A direction in the hemisphere around +z with density `cos(theta) / pi`, the distribution of
+/// light leaving a diffuse surface.
+pub fn cosine_hemisphere(u: (f64, f64)) -> Vec3 {
+    let r = u.0.sqrt();
+    let phi = 2.0 * PI * u.1;
+    Vec3::new(r * phi.cos(), r * phi.sin(), (1.0 - u.0).max(0.0).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_1d_sampling_follows_function() {
+        let dist = Distribution1D::new(vec![1.0, 0.0, 3.0]);
+        assert_eq!(dist.get_integral(), 4.0 / 3.0);
+
+        // A quarter of the mass is in the first step, the rest in the last.
+        let (x, pdf, offset) = dist.sample_continuous(0.125);
+        assert_eq!(offset, 0);
+        assert!((x - (0.5 / 3.0)).abs() < 1e-12);
+        assert_eq!(pdf, 0.75);
+        let (x, pdf, offset) = dist.sample_continuous(0.625);
+        assert_eq!(offset, 2);
+        assert!((x - (2.5 / 3.0)).abs() < 1e-12);
+        assert_eq!(pdf, 2.25);
+        assert_eq!(dist.pdf(0.5), 0.0);
+
+        assert_eq!(dist.sample_discrete(0.2), (0, 0.25));
+        assert_eq!(dist.sample_discrete(0.3), (2, 0.75));
+    }
+
+    #[test]
+    fn test_1d_zero_function_is_uniform() {
+        let dist = Distribution1D::new(vec![0.0; 4]);
+        let (x, pdf, _) = dist.sample_continuous(0.3);
+        assert!((x - 0.3).abs() < 1e-12);
+        assert_eq!(pdf, 1.0);
+    }
+
+    #[test]
+    fn test_directions_stay_inside() {
+        let cos_max = 0.9;
+        for i in 0..10 {
+            for j in 0..10 {
+                let u = (i as f64 / 10.0, j as f64 / 10.0);
+                let d = uniform_cone(u, cos_max);
+                assert!((d.length() - 1.0).abs() < 1e-12);
+                assert!(d.get_z() >= cos_max - 1e-12);
+                assert!((uniform_sphere(u).length() - 1.0).abs() < 1e-12);
+                let h = cosine_hemisphere(u);
+                assert!((h.length() - 1.0).abs() < 1e-12 && h.get_z() >= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_2d_pdf_matches_samples_and_integrates() {
+        let func = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 0.5, 0.0, 0.0, 9.0];
+        let dist = Distribution2D::new(&func, 4, 3);
+
+        let n = 48;
+        let mut integral = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = ((i as f64 + 0.5) / n as f64, (j as f64 + 0.5) / n as f64);
+                let (p, pdf) = dist.sample_continuous(u);
+                assert!((dist.pdf(p) - pdf).abs() < 1e-9);
+                integral += dist.pdf(u) / (n * n) as f64;
+            }
+        }
+        assert!((integral - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use crate::backgrounds::background::Background;
+use crate::lights::light::Light;
+use crate::objects::hittable::Hittable;
+use crate::objects::hittable_list::HittableList;
+
+/// Everything an integrator needs to know about what is being rendered.
+#[derive(Clone)]
+pub struct Scene {
+    world: Arc<dyn Hittable + Sync + Send>,
+    background: Arc<dyn Background + Sync + Send>,
+    lights: Vec<Arc<dyn Light + Sync + Send>>,
+    emitters: HittableList,
+}
+
+impl Scene {
+    #[inline(always)]
+    pub fn new(
+        world: Arc<dyn Hittable + Sync + Send>,
+        background: Arc<dyn Background + Sync + Send>,
+    ) -> Scene {
+        Scene {
+            world,
+            background,
+            lights: Vec::new(),
+            emitters: HittableList::new(),
+        }
+    }
+
+    /// Lights that are not part of the geometry, such as point and directional lights.
+    #[inline(always)]
+    pub fn with_lights(mut self, lights: Vec<Arc<dyn Light + Sync + Send>>) -> Scene {
+        self.lights = lights;
+        self
+    }
+
+    /// Emissive objects from the world that should be sampled directly. They must also be in
+    /// the world itself to be seen.
+    #[inline(always)]
+    pub fn with_emitters(mut self, emitters: HittableList) -> Scene {
+        self.emitters = emitters;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_world(&self) -> &dyn Hittable {
+        self.world.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn get_background(&self) -> &dyn Background {
+        self.background.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn get_lights(&self) -> &[Arc<dyn Light + Sync + Send>] {
+        &self.lights
+    }
+
+    #[inline(always)]
+    pub fn get_emitters(&self) -> &HittableList {
+        &self.emitters
+    }
+}
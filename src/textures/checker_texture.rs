@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::vec3::Point3;
+
+use crate::textures::solid_color::SolidColor;
+use crate::textures::texture::Texture;
+
+/// A solid checkerboard of `scale` sized cubes in space, so it wraps any shape without needing
+/// texture coordinates.
+pub struct CheckerTexture {
+    even: Arc<dyn Texture + Sync + Send>,
+    odd: Arc<dyn Texture + Sync + Send>,
+    scale: f64,
+}
+
+impl CheckerTexture {
+    #[inline(always)]
+    pub fn new(
+        even: Arc<dyn Texture + Sync + Send>,
+        odd: Arc<dyn Texture + Sync + Send>,
+        scale: f64,
+    ) -> CheckerTexture {
+        CheckerTexture { even, odd, scale }
+    }
+
+    pub fn from_colors(even: Color, odd: Color, scale: f64) -> CheckerTexture {
+        CheckerTexture::new(
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+            scale,
+        )
+    }
+
+    #[inline(always)]
+    pub fn get_scale(&self) -> f64 {
+        self.scale
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color {
+        let cell = |x: f64| (x / self.scale).floor() as i64;
+        let parity = cell(point.get_x()) + cell(point.get_y()) + cell(point.get_z());
+        if parity.rem_euclid(2) == 0 {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alternates_across_origin() {
+        let white = Color::new(255, 255, 255);
+        let black = Color::new(0, 0, 0);
+        let checker = CheckerTexture::from_colors(white, black, 0.5);
+        assert_eq!(
+            checker.value(0.0, 0.0, Point3::new(0.25, 0.25, 0.25)),
+            white
+        );
+        assert_eq!(
+            checker.value(0.0, 0.0, Point3::new(-0.25, 0.25, 0.25)),
+            black
+        );
+        assert_eq!(
+            checker.value(0.0, 0.0, Point3::new(-0.25, -0.25, 0.25)),
+            white
+        );
+        assert_eq!(
+            checker.value(0.0, 0.0, Point3::new(0.75, 0.25, 0.25)),
+            black
+        );
+    }
+}
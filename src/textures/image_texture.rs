@@ -0,0 +1,180 @@
+use std::convert::TryFrom;
+use std::path::Path;
+
+use image::{ImageError, RgbImage};
+
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+use crate::textures::texture::Texture;
+
+/// How texture coordinates outside `[0, 1]` are mapped back onto the image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WrapMode {
+    #[default]
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl WrapMode {
+    fn apply(self, i: i64, size: u32) -> u32 {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Mirror => {
+                let period = i.rem_euclid(2 * size);
+                if period < size {
+                    period
+                } else {
+                    (2 * size) - 1 - period
+                }
+            }
+            WrapMode::Clamp => i.clamp(0, size - 1),
+        };
+        wrapped as u32
+    }
+}
+
+// The furthest texel coordinate looked up, well inside what an `i64` holds.
+const MAX_TEXEL: f64 = (1_i64 << 40) as f64;
+
+/// An image mapped onto `(u, v)` with `v` pointing up, bilinearly filtered between texels.
+#[derive(Clone)]
+pub struct ImageTexture {
+    image: RgbImage,
+    wrap_u: WrapMode,
+    wrap_v: WrapMode,
+}
+
+impl ImageTexture {
+    #[inline(always)]
+    pub fn new(image: RgbImage) -> ImageTexture {
+        assert!(
+            image.width() > 0 && image.height() > 0,
+            "texture image is empty"
+        );
+        ImageTexture {
+            image,
+            wrap_u: WrapMode::default(),
+            wrap_v: WrapMode::default(),
+        }
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<ImageTexture, ImageError> {
+        Ok(ImageTexture::new(image::open(path)?.into_rgb8()))
+    }
+
+    #[inline(always)]
+    pub fn with_wrap_mode(mut self, wrap_u: WrapMode, wrap_v: WrapMode) -> ImageTexture {
+        self.wrap_u = wrap_u;
+        self.wrap_v = wrap_v;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_width(&self) -> u32 {
+        self.image.width()
+    }
+
+    #[inline(always)]
+    pub fn get_height(&self) -> u32 {
+        self.image.height()
+    }
+
+    #[inline(always)]
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let x = self.wrap_u.apply(x, self.image.width());
+        let y = self.wrap_v.apply(y, self.image.height());
+        let [r, g, b] = self.image.get_pixel(x, y).0;
+        Vec3::from(Color::new(r, g, b))
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: Point3) -> Color {
+        // Texel centers sit at half-integer coordinates, rows are stored top to bottom.
+        // Far out or broken coordinates are pulled into a range the texel indices can hold.
+        let finite = |t: f64| {
+            if t.is_finite() {
+                t.clamp(-MAX_TEXEL, MAX_TEXEL)
+            } else {
+                0.0
+            }
+        };
+        let x = finite((u * self.image.width() as f64) - 0.5);
+        let y = finite(((1.0 - v) * self.image.height() as f64) - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = ((1.0 - fx) * self.texel(x0, y0)) + (fx * self.texel(x0 + 1, y0));
+        let bottom = ((1.0 - fx) * self.texel(x0, y0 + 1)) + (fx * self.texel(x0 + 1, y0 + 1));
+        let c = ((1.0 - fy) * top) + (fy * bottom);
+        Color::try_from(Vec3::new(
+            c.get_x().min(1.0),
+            c.get_y().min(1.0),
+            c.get_z().min(1.0),
+        ))
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::Rgb;
+
+    fn gradient() -> ImageTexture {
+        // Black on the left, white on the right.
+        let mut image = RgbImage::new(2, 1);
+        image.put_pixel(0, 0, Rgb([0, 0, 0]));
+        image.put_pixel(1, 0, Rgb([255, 255, 255]));
+        ImageTexture::new(image)
+    }
+
+    #[test]
+    fn test_bilinear_between_texel_centers() {
+        let tex = gradient().with_wrap_mode(WrapMode::Clamp, WrapMode::Clamp);
+        let p = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(tex.value(0.25, 0.5, p), Color::new(0, 0, 0));
+        assert_eq!(tex.value(0.75, 0.5, p), Color::new(255, 255, 255));
+        assert_eq!(tex.value(0.5, 0.5, p), Color::new(127, 127, 127));
+        // Clamped past the edge, the last texel is repeated.
+        assert_eq!(tex.value(1.5, 0.5, p), Color::new(255, 255, 255));
+    }
+
+    #[test]
+    fn test_wrap_modes() {
+        assert_eq!(WrapMode::Repeat.apply(-1, 4), 3);
+        assert_eq!(WrapMode::Repeat.apply(5, 4), 1);
+        assert_eq!(WrapMode::Mirror.apply(-1, 4), 0);
+        assert_eq!(WrapMode::Mirror.apply(5, 4), 2);
+        assert_eq!(WrapMode::Clamp.apply(-3, 4), 0);
+        assert_eq!(WrapMode::Clamp.apply(9, 4), 3);
+
+        // Repeating, the left edge blends the first texel with the last.
+        let tex = gradient();
+        let p = Point3::new(0.0, 0.0, 0.0);
+        assert_eq!(tex.value(0.0, 0.5, p), Color::new(127, 127, 127));
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let p = Point3::new(0.0, 0.0, 0.0);
+        for mode in &[WrapMode::Repeat, WrapMode::Mirror, WrapMode::Clamp] {
+            let tex = gradient().with_wrap_mode(*mode, *mode);
+            for &(u, v) in &[
+                (f64::INFINITY, 0.5),
+                (f64::NEG_INFINITY, f64::INFINITY),
+                (f64::NAN, 0.5),
+                (1e300, -1e300),
+            ] {
+                tex.value(u, v, p);
+            }
+        }
+        let clamped = gradient().with_wrap_mode(WrapMode::Clamp, WrapMode::Clamp);
+        assert_eq!(clamped.value(1e300, 0.5, p), Color::new(255, 255, 255));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod checker_texture;
+pub mod image_texture;
+pub mod noise_texture;
+pub mod solid_color;
+pub mod texture;
@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::noise::{fbm, turbulence, Noise};
+use crate::vec3::{Point3, Vec3};
+
+use crate::textures::texture::Texture;
+
+/// How a noise field is turned into a blend factor in `[0, 1]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoisePattern {
+    /// The raw noise.
+    Plain,
+    /// Layered octaves for clouds and terrain.
+    Fbm {
+        octaves: u32,
+        lacunarity: f64,
+        gain: f64,
+    },
+    /// Absolute octaves, crinkly like smoke.
+    Turbulence { depth: u32 },
+    /// Sine stripes along z warped by turbulence.
+    Marble { depth: u32 },
+    /// Concentric rings around the y axis warped by turbulence.
+    Wood { rings: f64, depth: u32 },
+}
+
+/// Blends two colors with a noise pattern evaluated at the hit point, so it needs no uvs.
+pub struct NoiseTexture {
+    noise: Arc<dyn Noise + Sync + Send>,
+    pattern: NoisePattern,
+    scale: f64,
+    low: Color,
+    high: Color,
+}
+
+impl NoiseTexture {
+    /// A black to white texture, `scale` is the feature frequency.
+    #[inline(always)]
+    pub fn new(
+        noise: Arc<dyn Noise + Sync + Send>,
+        pattern: NoisePattern,
+        scale: f64,
+    ) -> NoiseTexture {
+        NoiseTexture {
+            noise,
+            pattern,
+            scale,
+            low: Color::new(0, 0, 0),
+            high: Color::new(255, 255, 255),
+        }
+    }
+
+    #[inline(always)]
+    pub fn with_colors(mut self, low: Color, high: Color) -> NoiseTexture {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_pattern(&self) -> NoisePattern {
+        self.pattern
+    }
+
+    fn blend_factor(&self, point: Point3) -> f64 {
+        let p = self.scale * point;
+        let noise = self.noise.as_ref();
+        let factor = match self.pattern {
+            NoisePattern::Plain => 0.5 * (1.0 + noise.noise(p)),
+            NoisePattern::Fbm {
+                octaves,
+                lacunarity,
+                gain,
+            } => 0.5 * (1.0 + fbm(noise, p, octaves, lacunarity, gain)),
+            NoisePattern::Turbulence { depth } => turbulence(noise, p, depth),
+            NoisePattern::Marble { depth } => {
+                0.5 * (1.0 + (p.get_z() + (10.0 * turbulence(noise, p, depth))).sin())
+            }
+            NoisePattern::Wood { rings, depth } => {
+                let radius = p.get_x().hypot(p.get_z());
+                let grain = rings * (radius + (0.5 * turbulence(noise, p, depth)));
+                grain - grain.floor()
+            }
+        };
+        factor.clamp(0.0, 1.0)
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: Point3) -> Color {
+        let t = self.blend_factor(point);
+        Color::try_from(((1.0 - t) * Vec3::from(self.low)) + (t * Vec3::from(self.high)))
+            .unwrap_or(self.high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::noise::Perlin;
+
+    #[test]
+    fn test_patterns_stay_between_colors() {
+        let perlin: Arc<dyn Noise + Sync + Send> =
+            Arc::new(Perlin::new_with_gen(&mut StdRng::seed_from_u64(11)));
+        let low = Color::new(20, 40, 60);
+        let high = Color::new(200, 180, 160);
+        let patterns = [
+            NoisePattern::Plain,
+            NoisePattern::Fbm {
+                octaves: 6,
+                lacunarity: 2.0,
+                gain: 0.5,
+            },
+            NoisePattern::Turbulence { depth: 7 },
+            NoisePattern::Marble { depth: 7 },
+            NoisePattern::Wood {
+                rings: 8.0,
+                depth: 3,
+            },
+        ];
+        for pattern in &patterns {
+            let tex = NoiseTexture::new(perlin.clone(), *pattern, 4.0).with_colors(low, high);
+            for i in 0..100 {
+                let p = Point3::new(i as f64 * 0.37, (i as f64 * 0.11).sin(), i as f64 * -0.05);
+                let c = tex.value(0.0, 0.0, p);
+                assert!((low.get_red()..=high.get_red()).contains(&c.get_red()));
+                assert!((low.get_blue()..=high.get_blue()).contains(&c.get_blue()));
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::color::Color;
+use crate::vec3::Point3;
+
+use crate::textures::texture::Texture;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    #[inline(always)]
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+
+    #[inline(always)]
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+}
+
+impl From<Color> for SolidColor {
+    #[inline(always)]
+    fn from(color: Color) -> SolidColor {
+        SolidColor::new(color)
+    }
+}
+
+impl Texture for SolidColor {
+    #[inline(always)]
+    fn value(&self, _u: f64, _v: f64, _point: Point3) -> Color {
+        self.color
+    }
+}
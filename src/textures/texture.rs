@@ -0,0 +1,7 @@
+use crate::color::Color;
+use crate::vec3::Point3;
+
+pub trait Texture {
+    /// The color at surface coordinates `(u, v)`, which lie at `point` in world space.
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color;
+}
@@ -0,0 +1,279 @@
+use std::str::FromStr;
+
+use crate::color::LinearRgb;
+
+/// A curve that squeezes unbounded radiance into the `[0, 1]` a display can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToneMapOperator {
+    /// No curve, everything brighter than one is clipped.
+    #[default]
+    Clamp,
+    /// `x / (1 + x)` per channel, never quite reaching white.
+    Reinhard,
+    /// Reinhard with the given radiance mapped to pure white.
+    ExtendedReinhard { white: f32 },
+    /// John Hable's filmic curve from Uncharted 2.
+    Hable,
+    /// Stephen Hill's fit of the ACES reference rendering and sRGB output transforms.
+    AcesFilmic,
+    /// Troy Sobotka's AgX, which desaturates bright colors towards white instead of skewing
+    /// their hue.
+    Agx,
+}
+
+impl FromStr for ToneMapOperator {
+    type Err = String;
+
+    /// Parses `clamp`, `reinhard`, `extended-reinhard` with an optional white point as in
+    /// `extended-reinhard:8`, `hable`, `aces` or `agx`.
+    fn from_str(s: &str) -> Result<ToneMapOperator, String> {
+        let (name, white) = match s.find(':') {
+            Some(i) => (&s[..i], Some(&s[(i + 1)..])),
+            None => (s, None),
+        };
+        match (name.to_ascii_lowercase().as_str(), white) {
+            ("clamp", None) => Ok(ToneMapOperator::Clamp),
+            ("reinhard", None) => Ok(ToneMapOperator::Reinhard),
+            ("extended-reinhard", white) => {
+                let white = match white {
+                    Some(w) => w
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|w| *w > 0.0 && w.is_finite())
+                        .ok_or_else(|| format!("invalid white point '{}'", w))?,
+                    None => 4.0,
+                };
+                Ok(ToneMapOperator::ExtendedReinhard { white })
+            }
+            ("hable", None) => Ok(ToneMapOperator::Hable),
+            ("aces", None) => Ok(ToneMapOperator::AcesFilmic),
+            ("agx", None) => Ok(ToneMapOperator::Agx),
+            _ => Err(format!("unknown tone mapping operator '{}'", s)),
+        }
+    }
+}
+
+/// The post-process between the film and an 8-bit image: exposure, a tone curve and the sRGB
+/// transfer function.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ToneMapper {
+    operator: ToneMapOperator,
+    exposure: f32,
+}
+
+impl ToneMapper {
+    #[inline(always)]
+    pub fn new(operator: ToneMapOperator) -> ToneMapper {
+        ToneMapper {
+            operator,
+            exposure: 0.0,
+        }
+    }
+
+    /// Brightens the image by `stops` before the curve, each stop doubling it.
+    #[inline(always)]
+    pub fn with_exposure(mut self, stops: f32) -> ToneMapper {
+        self.exposure = stops;
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_operator(&self) -> ToneMapOperator {
+        self.operator
+    }
+
+    #[inline(always)]
+    pub fn get_exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Exposure and tone curve, leaving linear values in `[0, 1]`.
+    pub fn tone_map(&self, c: LinearRgb) -> LinearRgb {
+        let scale = self.exposure.exp2();
+        let c = c.map(|x| (x * scale).max(0.0));
+        match self.operator {
+            ToneMapOperator::Clamp => c.map(|x| x.min(1.0)),
+            ToneMapOperator::Reinhard => c.map(|x| x / (1.0 + x)),
+            ToneMapOperator::ExtendedReinhard { white } => {
+                let white_squared = white * white;
+                c.map(|x| ((x * (1.0 + (x / white_squared))) / (1.0 + x)).min(1.0))
+            }
+            ToneMapOperator::Hable => {
+                let white_scale = 1.0 / hable_partial(HABLE_WHITE);
+                c.map(|x| (hable_partial(2.0 * x) * white_scale).min(1.0))
+            }
+            ToneMapOperator::AcesFilmic => aces_fitted(c),
+            ToneMapOperator::Agx => agx(c),
+        }
+    }
+
+    /// `tone_map` followed by the sRGB transfer function, ready to be quantized.
+    #[inline(always)]
+    pub fn to_display(&self, c: LinearRgb) -> LinearRgb {
+        self.tone_map(c).map(srgb_oetf)
+    }
+}
+
+/// The sRGB opto-electronic transfer function, from linear light to encoded values.
+#[inline(always)]
+pub fn srgb_oetf(x: f32) -> f32 {
+    if x <= 0.003_130_8 {
+        12.92 * x
+    } else {
+        (1.055 * x.powf(1.0 / 2.4)) - 0.055
+    }
+}
+
+// Linear radiance that Hable's curve maps to white.
+const HABLE_WHITE: f32 = 11.2;
+
+fn hable_partial(x: f32) -> f32 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    (((x * ((a * x) + (c * b))) + (d * e)) / ((x * ((a * x) + b)) + (d * f))) - (e / f)
+}
+
+type Matrix3 = [[f32; 3]; 3];
+
+#[inline(always)]
+fn mul(m: &Matrix3, c: LinearRgb) -> LinearRgb {
+    let row = |r: &[f32; 3]| (r[0] * c.get_red()) + (r[1] * c.get_green()) + (r[2] * c.get_blue());
+    LinearRgb::new(row(&m[0]), row(&m[1]), row(&m[2]))
+}
+
+// sRGB to the ACES rendering space, with the RRT's saturation adjustment folded in.
+const ACES_INPUT: Matrix3 = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+];
+
+// Back from the ODT's output space to linear sRGB.
+const ACES_OUTPUT: Matrix3 = [
+    [1.60475, -0.53108, -0.07367],
+    [-0.10208, 1.10813, -0.00605],
+    [-0.00327, -0.07276, 1.07602],
+];
+
+fn aces_fitted(c: LinearRgb) -> LinearRgb {
+    let rrt_and_odt = |v: f32| {
+        let a = (v * (v + 0.024_578_6)) - 0.000_090_537;
+        let b = (v * ((0.983_729 * v) + 0.432_951)) + 0.238_081;
+        a / b
+    };
+    mul(&ACES_OUTPUT, mul(&ACES_INPUT, c).map(rrt_and_odt)).map(|x| x.clamp(0.0, 1.0))
+}
+
+// Into and out of AgX's slightly desaturated working space.
+const AGX_INSET: Matrix3 = [
+    [0.842_479_1, 0.078_433_6, 0.079_223_75],
+    [0.042_328_24, 0.878_468_6, 0.079_166_13],
+    [0.042_375_65, 0.078_433_6, 0.879_143],
+];
+
+const AGX_OUTSET: Matrix3 = [
+    [1.196_879, -0.098_020_88, -0.099_029_74],
+    [-0.052_896_85, 1.151_903_1, -0.098_961_18],
+    [-0.052_971_64, -0.098_043_45, 1.151_073_7],
+];
+
+// The range of exposures, in stops around middle grey, that the curve covers.
+const AGX_MIN_EV: f32 = -12.473_93;
+const AGX_MAX_EV: f32 = 4.026_069;
+
+fn agx(c: LinearRgb) -> LinearRgb {
+    let contrast = |x: f32| {
+        let x = (x.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV) - AGX_MIN_EV)
+            / (AGX_MAX_EV - AGX_MIN_EV);
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        (15.5 * x4 * x2) - (40.14 * x4 * x) + (31.96 * x4) - (6.868 * x2 * x)
+            + (0.4298 * x2)
+            + (0.1191 * x)
+            - 0.002_32
+    };
+    // The curve produces display encoded values, decoded again so `to_display` can apply the
+    // same transfer function as every other operator.
+    mul(&AGX_OUTSET, mul(&AGX_INSET, c).map(contrast)).map(|x| x.clamp(0.0, 1.0).powf(2.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [ToneMapOperator; 6] = [
+        ToneMapOperator::Clamp,
+        ToneMapOperator::Reinhard,
+        ToneMapOperator::ExtendedReinhard { white: 4.0 },
+        ToneMapOperator::Hable,
+        ToneMapOperator::AcesFilmic,
+        ToneMapOperator::Agx,
+    ];
+
+    fn grey(x: f32) -> LinearRgb {
+        LinearRgb::new(x, x, x)
+    }
+
+    #[test]
+    fn test_srgb_oetf() {
+        assert_eq!(srgb_oetf(0.0), 0.0);
+        assert!((srgb_oetf(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_oetf(0.18) - 0.4614).abs() < 1e-3);
+        // Both pieces meet at the threshold.
+        let below = srgb_oetf(0.003_130_8);
+        let above = (1.055 * 0.003_130_8_f32.powf(1.0 / 2.4)) - 0.055;
+        assert!((below - above).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_curves_are_monotonic_and_bounded() {
+        for operator in &ALL {
+            let mapper = ToneMapper::new(*operator);
+            let mut previous = -1.0;
+            for i in 0..200 {
+                let x = 0.001 * 1.08_f32.powi(i);
+                let y = mapper.tone_map(grey(x)).get_green();
+                assert!((0.0..=1.0).contains(&y), "{:?}({}) = {}", operator, x, y);
+                assert!(y >= previous - 1e-6, "{:?} falls at {}", operator, x);
+                previous = y;
+            }
+            assert!(mapper.tone_map(grey(0.0)).get_green() < 0.01);
+            assert!(mapper.tone_map(grey(1e4)).get_green() > 0.9);
+        }
+    }
+
+    #[test]
+    fn test_reinhard_and_exposure() {
+        let reinhard = ToneMapper::new(ToneMapOperator::Reinhard);
+        assert_eq!(reinhard.tone_map(grey(1.0)), grey(0.5));
+        assert_eq!(reinhard.with_exposure(1.0).tone_map(grey(0.5)), grey(0.5));
+
+        let extended = ToneMapper::new(ToneMapOperator::ExtendedReinhard { white: 4.0 });
+        assert_eq!(extended.tone_map(grey(4.0)), grey(1.0));
+        assert_eq!(extended.tone_map(grey(40.0)), grey(1.0));
+
+        let hable = ToneMapper::new(ToneMapOperator::Hable);
+        assert!((hable.tone_map(grey(HABLE_WHITE / 2.0)).get_red() - 1.0).abs() < 1e-5);
+
+        let clamp = ToneMapper::default().to_display(LinearRgb::new(2.0, -1.0, 0.5));
+        assert!((clamp.get_red() - 1.0).abs() < 1e-6);
+        assert_eq!(clamp.get_green(), 0.0);
+        assert!((clamp.get_blue() - srgb_oetf(0.5)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_operator() {
+        assert_eq!("aces".parse(), Ok(ToneMapOperator::AcesFilmic));
+        assert_eq!("AgX".parse(), Ok(ToneMapOperator::Agx));
+        assert_eq!(
+            "extended-reinhard".parse(),
+            Ok(ToneMapOperator::ExtendedReinhard { white: 4.0 })
+        );
+        assert_eq!(
+            "extended-reinhard:8".parse(),
+            Ok(ToneMapOperator::ExtendedReinhard { white: 8.0 })
+        );
+        assert!("extended-reinhard:-1".parse::<ToneMapOperator>().is_err());
+        assert!("hable:2".parse::<ToneMapOperator>().is_err());
+        assert!("filmic".parse::<ToneMapOperator>().is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use std::ops::Mul;
+
+use crate::objects::aabb::AABB;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// An affine transform stored as a 4x4 row-major matrix together with its inverse, so both
+/// directions are available without inverting again at render time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Transform {
+    #[inline(always)]
+    pub fn identity() -> Transform {
+        Transform {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+
+    /// Builds a transform from an arbitrary matrix, `None` if it cannot be inverted.
+    pub fn from_matrix(matrix: [[f64; 4]; 4]) -> Option<Transform> {
+        invert(&matrix).map(|inverse| Transform { matrix, inverse })
+    }
+
+    pub fn translate(offset: Vec3) -> Transform {
+        let (x, y, z) = (offset.get_x(), offset.get_y(), offset.get_z());
+        Transform {
+            matrix: [
+                [1.0, 0.0, 0.0, x],
+                [0.0, 1.0, 0.0, y],
+                [0.0, 0.0, 1.0, z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            inverse: [
+                [1.0, 0.0, 0.0, -x],
+                [0.0, 1.0, 0.0, -y],
+                [0.0, 0.0, 1.0, -z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn scale(factors: Vec3) -> Transform {
+        let (x, y, z) = (factors.get_x(), factors.get_y(), factors.get_z());
+        Transform {
+            matrix: [
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+            inverse: [
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 1.0 / z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Counter-clockwise rotation about `axis` when looking down the axis towards the origin.
+    pub fn rotate(axis: Vec3, degrees: f64) -> Transform {
+        let a = axis.unit_vector();
+        let (x, y, z) = (a.get_x(), a.get_y(), a.get_z());
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let matrix = [
+            [
+                cos + (x * x * (1.0 - cos)),
+                (x * y * (1.0 - cos)) - (z * sin),
+                (x * z * (1.0 - cos)) + (y * sin),
+                0.0,
+            ],
+            [
+                (y * x * (1.0 - cos)) + (z * sin),
+                cos + (y * y * (1.0 - cos)),
+                (y * z * (1.0 - cos)) - (x * sin),
+                0.0,
+            ],
+            [
+                (z * x * (1.0 - cos)) - (y * sin),
+                (z * y * (1.0 - cos)) + (x * sin),
+                cos + (z * z * (1.0 - cos)),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        // Rotations are orthogonal, the inverse is the transpose.
+        Transform {
+            matrix,
+            inverse: transpose(&matrix),
+        }
+    }
+
+    #[inline(always)]
+    pub fn rotate_x(degrees: f64) -> Transform {
+        Transform::rotate(Vec3::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    #[inline(always)]
+    pub fn rotate_y(degrees: f64) -> Transform {
+        Transform::rotate(Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    #[inline(always)]
+    pub fn rotate_z(degrees: f64) -> Transform {
+        Transform::rotate(Vec3::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    #[inline(always)]
+    pub fn get_matrix(&self) -> [[f64; 4]; 4] {
+        self.matrix
+    }
+
+    #[inline(always)]
+    pub fn inverse(&self) -> Transform {
+        Transform {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    /// `self.then(other)` applies `self` first and `other` second.
+    #[inline(always)]
+    pub fn then(&self, other: &Transform) -> Transform {
+        *other * *self
+    }
+
+    #[inline(always)]
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        apply(&self.matrix, p, 1.0)
+    }
+
+    #[inline(always)]
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        apply(&self.matrix, v, 0.0)
+    }
+
+    /// Normals stay perpendicular to surfaces under non-uniform scaling only when multiplied by
+    /// the inverse transpose. The result is not normalized.
+    #[inline(always)]
+    pub fn transform_normal(&self, n: Vec3) -> Vec3 {
+        let m = &self.inverse;
+        Vec3::new(
+            (m[0][0] * n.get_x()) + (m[1][0] * n.get_y()) + (m[2][0] * n.get_z()),
+            (m[0][1] * n.get_x()) + (m[1][1] * n.get_y()) + (m[2][1] * n.get_z()),
+            (m[0][2] * n.get_x()) + (m[1][2] * n.get_y()) + (m[2][2] * n.get_z()),
+        )
+    }
+
+    /// The direction is transformed without normalizing, so distances along the ray are the same
+    /// in both spaces.
+    #[inline(always)]
+    pub fn transform_ray(&self, r: &Ray) -> Ray {
+        Ray::new(
+            self.transform_point(r.get_origin()),
+            self.transform_vector(r.get_direction()),
+            r.get_time(),
+        )
+    }
+
+    pub fn transform_aabb(&self, bbox: &AABB) -> AABB {
+        let (low, high) = (bbox.get_low(), bbox.get_high());
+        (0..8)
+            .map(|corner| {
+                let p = Point3::new(
+                    if corner & 1 == 0 {
+                        low.get_x()
+                    } else {
+                        high.get_x()
+                    },
+                    if corner & 2 == 0 {
+                        low.get_y()
+                    } else {
+                        high.get_y()
+                    },
+                    if corner & 4 == 0 {
+                        low.get_z()
+                    } else {
+                        high.get_z()
+                    },
+                );
+                AABB::from_point(self.transform_point(p))
+            })
+            .reduce(AABB::surrounding_box)
+            .unwrap()
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::identity()
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+
+    /// `a * b` applies `b` first, matching matrix multiplication order.
+    fn mul(self, rhs: Transform) -> Transform {
+        Transform {
+            matrix: multiply(&self.matrix, &rhs.matrix),
+            inverse: multiply(&rhs.inverse, &self.inverse),
+        }
+    }
+}
+
+#[inline(always)]
+fn apply(m: &Matrix, v: Vec3, w: f64) -> Vec3 {
+    let row =
+        |r: &[f64; 4]| (r[0] * v.get_x()) + (r[1] * v.get_y()) + (r[2] * v.get_z()) + (r[3] * w);
+    Vec3::new(row(&m[0]), row(&m[1]), row(&m[2]))
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut out = [[0.0; 4]; 4];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+fn transpose(m: &Matrix) -> Matrix {
+    let mut out = [[0.0; 4]; 4];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = m[j][i];
+        }
+    }
+    out
+}
+
+// Gauss-Jordan elimination with partial pivoting.
+fn invert(m: &Matrix) -> Option<Matrix> {
+    let mut a = *m;
+    let mut inv = IDENTITY;
+    for col in 0..4 {
+        let pivot = (col..4)
+            .max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())
+            .unwrap();
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+
+        let scale = 1.0 / a[col][col];
+        for k in 0..4 {
+            a[col][k] *= scale;
+            inv[col][k] *= scale;
+        }
+        for row in 0..4 {
+            if row != col {
+                let factor = a[row][col];
+                for k in 0..4 {
+                    a[row][k] -= factor * a[col][k];
+                    inv[row][k] -= factor * inv[col][k];
+                }
+            }
+        }
+    }
+    Some(inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_compose_order() {
+        let t = Transform::rotate_y(90.0).then(&Transform::translate(Vec3::new(0.0, 0.0, 5.0)));
+        assert_near(
+            t.transform_point(Point3::new(1.0, 0.0, 0.0)),
+            Point3::new(0.0, 0.0, 4.0),
+        );
+        assert_near(
+            t.transform_vector(Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+    }
+
+    #[test]
+    fn test_from_matrix_inverse() {
+        let t = Transform::translate(Vec3::new(1.0, 2.0, 3.0))
+            * Transform::rotate(Vec3::new(1.0, 1.0, 0.0), 30.0)
+            * Transform::scale(Vec3::new(2.0, 3.0, 0.5));
+        let rebuilt = Transform::from_matrix(t.get_matrix()).unwrap();
+        let p = Point3::new(-4.0, 0.5, 7.0);
+        assert_near(rebuilt.inverse().transform_point(t.transform_point(p)), p);
+        assert!(Transform::from_matrix([[0.0; 4]; 4]).is_none());
+    }
+
+    #[test]
+    fn test_normal_under_non_uniform_scale() {
+        let t = Transform::scale(Vec3::new(4.0, 1.0, 1.0));
+        // A plane through the origin containing (1, 1, 0) and z.
+        let tangent = t.transform_vector(Vec3::new(1.0, 1.0, 0.0));
+        let normal = t.transform_normal(Vec3::new(1.0, -1.0, 0.0));
+        assert!(tangent.dot(&normal).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_transform_aabb() {
+        let bbox = AABB::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let rotated = Transform::rotate_z(45.0).transform_aabb(&bbox);
+        let r = 2.0_f64.sqrt();
+        assert_near(rotated.get_low(), Point3::new(-r, -r, -1.0));
+        assert_near(rotated.get_high(), Point3::new(r, r, 1.0));
+    }
+}
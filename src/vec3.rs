@@ -3,7 +3,7 @@ use std::ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub,
 
 use rand::{thread_rng, Rng};
 
-use super::color::Color;
+use super::color::{Color, LinearRgb};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vec3 {
@@ -101,9 +101,15 @@ impl Vec3 {
         self.x.powi(2) + self.y.powi(2) + self.z.powi(2)
     }
 
+    /// Rec. 709 relative luminance, for a vector holding a linear color.
+    #[inline(always)]
+    pub fn luminance(&self) -> f64 {
+        (0.2126 * self.x) + (0.7152 * self.y) + (0.0722 * self.z)
+    }
+
     #[inline(always)]
     pub fn unit_vector(&self) -> Vec3 {
-        return *self / self.length();
+        *self / self.length()
     }
 
     #[inline(always)]
@@ -306,6 +312,16 @@ impl From<Color> for Vec3 {
     }
 }
 
+impl From<LinearRgb> for Vec3 {
+    fn from(c: LinearRgb) -> Self {
+        Vec3::new(
+            c.get_red() as f64,
+            c.get_green() as f64,
+            c.get_blue() as f64,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;